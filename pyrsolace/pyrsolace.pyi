@@ -1,4 +1,4 @@
-from typing import Callable, Optional, List, Any
+from typing import Callable, Optional, List, Any, Iterator
 from enum import Enum
 
 class LogLevel(Enum):
@@ -85,6 +85,16 @@ class Event:
     session_event: SessionEvent
     def get_session_event_string(self) -> str: ...
 
+class SlowConsumerEvent:
+    channel: str
+    capacity: Optional[int]
+    overflow: str
+
+class SlowConsumerReceiver:
+    def recv(self) -> SlowConsumerEvent: ...
+    def len(self) -> int: ...
+    def dropped(self) -> int: ...
+
 class Dest:
     def __init__(self, dest_type: DestType, dest: str): ...
     def get_dest_type(self) -> DestType: ...
@@ -114,6 +124,11 @@ class Msg:
     recv_timestamp: Optional[int]
     seq: Optional[int]
     msg_type: Optional[str]
+    is_redelivered: bool
+    delivery_count: Optional[int]
+    dmq_eligible: bool
+    ttl: Optional[int]
+    expiration: Optional[int]
 
     def __init__(
         self,
@@ -146,6 +161,12 @@ class AsyncEventReceiver:
     async def recv(self) -> Event: ...
     def len(self) -> int: ...
 
+class QueueBrowser:
+    def next(self) -> Optional[Msg]: ...
+    def __iter__(self) -> Iterator[Msg]: ...
+    def __next__(self) -> Msg: ...
+    def delete(self, msg: Msg) -> None: ...
+
 class Client:
     def __init__(self): ...
     def set_msg_callback(self, callback: Callable[[Msg], None]) -> None: ...
@@ -187,7 +208,10 @@ class Client:
         oauth2_access_token: Optional[str] = None,
         oauth2_issuer_identifier: Optional[str] = None,
         oidc_id_token: Optional[str] = None,
-    ) -> bool: ...
+    ) -> bool:
+        """Connect to the broker. Returns True once connected, raises with the
+        sub code and error string (e.g. LoginFailure) when the connect fails."""
+        ...
     def disconnect(self) -> None: ...
     def subscribe(self, topic: str) -> ReturnCode: ...
     def unsubscribe(self, topic: str) -> ReturnCode: ...
@@ -210,7 +234,11 @@ class Client:
     def modify_client_info(
         self, app_description: Optional[str], client_name: Optional[str]
     ) -> ReturnCode: ...
+    def create_queue_browser(
+        self, queue: str, wait_ms: int = 1000
+    ) -> QueueBrowser: ...
     def get_event_receiver(self) -> EventReceiver: ...
+    def get_slow_consumer_receiver(self, capacity: int = 64) -> SlowConsumerReceiver: ...
     def get_msg_receiver(self) -> MsgReceiver: ...
     def get_p2p_receiver(self) -> MsgReceiver: ...
     def get_request_receiver(self) -> MsgReceiver: ...
@@ -1,6 +1,7 @@
 #![allow(non_local_definitions)]
 
 use std::sync::Arc;
+use std::num::NonZeroUsize;
 use std::borrow::Cow;
 use std::time::Duration;
 use std::thread::JoinHandle;
@@ -14,6 +15,10 @@ use pyo3::exceptions::PyException;
 use pyo3_asyncio::tokio::future_into_py;
 
 use rsolace::solclient::{SolClient, SolClientError};
+use rsolace::solbrowser::QueueBrowser as SolQueueBrowser;
+use rsolace::solbus::BusReceiver;
+use rsolace::solconfig::SlowConsumerEvent as SolSlowConsumerEvent;
+use rsolace::solflow::SolFlowError;
 use rsolace::SessionProps;
 use rsolace::solevent::SolEvent;
 use rsolace::solmsg::{Destination, SolMsg};
@@ -113,6 +118,20 @@ impl From<SolClientError> for PySolClientError {
     }
 }
 
+struct PySolFlowError(SolFlowError);
+
+impl From<PySolFlowError> for PyErr {
+    fn from(error: PySolFlowError) -> Self {
+        PyException::new_err(format!("SolFlowError: {:?}", error.0))
+    }
+}
+
+impl From<SolFlowError> for PySolFlowError {
+    fn from(other: SolFlowError) -> Self {
+        Self(other)
+    }
+}
+
 #[pyclass]
 #[derive(Debug, Clone, Copy)]
 struct CacheStatus(SolClientCacheStatus);
@@ -854,6 +873,52 @@ impl EventReceiver {
     }
 }
 
+/// Raised when a receive channel of the client starts overflowing.
+#[pyclass]
+struct SlowConsumerEvent(SolSlowConsumerEvent);
+
+#[pymethods]
+impl SlowConsumerEvent {
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+
+    #[getter]
+    fn channel(&self) -> &'static str {
+        self.0.channel
+    }
+
+    #[getter]
+    fn capacity(&self) -> Option<usize> {
+        self.0.capacity
+    }
+
+    #[getter]
+    fn overflow(&self) -> String {
+        format!("{:?}", self.0.overflow)
+    }
+}
+
+#[pyclass]
+struct SlowConsumerReceiver(BusReceiver<SolSlowConsumerEvent>);
+
+#[pymethods]
+impl SlowConsumerReceiver {
+    fn recv(&self, py: Python) -> PyResult<SlowConsumerEvent> {
+        py.allow_threads(|| {
+            self.0.recv().map_err(|e| ReceiverError(e).into()).map(SlowConsumerEvent)
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn dropped(&self) -> u64 {
+        self.0.dropped()
+    }
+}
+
 #[pyclass]
 struct AsyncEventReceiver(AsyncReceiver<SolEvent>);
 
@@ -1117,6 +1182,41 @@ impl Msg {
         self.0.is_discard_indication()
     }
 
+    #[getter(is_redelivered)]
+    fn get_is_redelivered(&self) -> bool {
+        self.0.is_redelivered()
+    }
+
+    #[getter(delivery_count)]
+    fn get_delivery_count(&self) -> Option<i32> {
+        self.0.get_delivery_count().ok()
+    }
+
+    #[getter(dmq_eligible)]
+    fn get_dmq_eligible(&self) -> bool {
+        self.0.is_dmq_eligible()
+    }
+
+    #[setter(dmq_eligible)]
+    fn set_dmq_eligible(&mut self, dmq_eligible: bool) {
+        self.0.set_dmq_eligible(dmq_eligible);
+    }
+
+    #[getter(ttl)]
+    fn get_ttl(&self) -> Option<i64> {
+        self.0.get_ttl().ok()
+    }
+
+    #[setter(ttl)]
+    fn set_ttl(&mut self, ttl: i64) {
+        self.0.set_ttl(ttl);
+    }
+
+    #[getter(expiration)]
+    fn get_expiration(&self) -> Option<i64> {
+        self.0.get_expiration().ok()
+    }
+
     
     fn get_user_prop(&self, key: &str) -> PyResult<String> {
         self.0.get_user_prop(key).map_err(|e| {
@@ -1158,6 +1258,32 @@ impl Msg {
 }
 
 
+#[pyclass]
+struct QueueBrowser(SolQueueBrowser);
+
+#[pymethods]
+impl QueueBrowser {
+    fn __repr__(&self) -> String {
+        format!("QueueBrowser(wait={:?})", self.0.wait())
+    }
+
+    fn next(&self, py: Python) -> Option<Msg> {
+        py.allow_threads(|| self.0.next_msg().map(Msg::new))
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python) -> Option<Msg> {
+        self.next(py)
+    }
+
+    fn delete(&self, msg: &Msg) -> Result<(), PySolFlowError> {
+        self.0.delete(&msg.0).map_err(PySolFlowError::from)
+    }
+}
+
 #[pyclass(name = "Client")]
 struct Client {
     solclient: SolClient,
@@ -1684,7 +1810,7 @@ impl Client {
         oauth2_access_token: Option<&str>,
         oauth2_issuer_identifier: Option<&str>,
         oidc_id_token: Option<&str>,
-    ) -> bool {
+    ) -> Result<bool, PySolClientError> {
         let mut props = SessionProps::default()
             .username(username)
             .password(password)
@@ -1724,9 +1850,9 @@ impl Client {
             .oauth2_issuer_identifier(oauth2_issuer_identifier)
             .oidc_id_token(oidc_id_token);
 
-        let r = self.solclient.connect(props);
+        self.solclient.connect(props).map_err(PySolClientError::from)?;
         self.is_connected = true;
-        r
+        Ok(true)
     }
 
     #[pyo3(signature = ())]
@@ -1789,9 +1915,22 @@ impl Client {
         })
     }
 
+    #[pyo3(signature = (queue, wait_ms=1000))]
+    fn create_queue_browser(&self, queue: &str, wait_ms: u64) -> Result<QueueBrowser, PySolFlowError> {
+        let browser = self.solclient.create_queue_browser(queue, Duration::from_millis(wait_ms)).map_err(PySolFlowError::from)?;
+        Ok(QueueBrowser(browser))
+    }
+
     fn get_event_receiver(&self) -> EventReceiver {
         EventReceiver(self.solclient.get_event_receiver())
     }
+
+    #[pyo3(signature = (capacity=64))]
+    fn get_slow_consumer_receiver(&self, capacity: usize) -> PyResult<SlowConsumerReceiver> {
+        let capacity = NonZeroUsize::new(capacity)
+            .ok_or_else(|| PyException::new_err("capacity must be greater than 0"))?;
+        Ok(SlowConsumerReceiver(self.solclient.get_slow_consumer_subscription(capacity)))
+    }
     
     fn get_msg_receiver(&self) -> MsgReceiver {
         MsgReceiver(self.solclient.get_msg_receiver())
@@ -1866,6 +2005,9 @@ fn pyrsolace(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<MsgReceiver>()?;
     m.add_class::<AsyncMsgReceiver>()?;
     m.add_class::<AsyncEventReceiver>()?;
+    m.add_class::<SlowConsumerEvent>()?;
+    m.add_class::<SlowConsumerReceiver>()?;
+    m.add_class::<QueueBrowser>()?;
     m.add_class::<DeliveryMode>()?;
     m.add_class::<SessionEvent>()?;
     m.add_class::<DestType>()?;
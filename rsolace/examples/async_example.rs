@@ -43,9 +43,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .password(&std::env::var("SOLACE_PASSWORD").unwrap_or("".to_string()))
         .compression_level(5);
 
-    // Connect to Solace
-    let connected = solclient.connect(props);
-    if connected {
+    // Connect to Solace without blocking the runtime
+    if let Err(e) = solclient.connect_async(props).await {
+        tracing::error!("Failed to connect to Solace: {}", e);
+    } else {
         tracing::info!("Connected to Solace successfully!");
 
         // Subscribe to a topic
@@ -57,8 +58,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
         // Wait a bit to receive messages
         tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
-    } else {
-        tracing::error!("Failed to connect to Solace");
     }
 
     // Clean up
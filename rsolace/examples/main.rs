@@ -69,7 +69,7 @@ fn main() {
                 .compression_level(5);
 
             let r = solclient.connect(props);
-            tracing::info!("connect: {}", r);
+            tracing::info!("connect: {:?}", r);
 
             // solclient.set_rx_msg_callback(func)
             solclient.subscribe_ext(
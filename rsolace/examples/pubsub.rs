@@ -72,7 +72,7 @@ fn main() {
                 });
             }
             let r = solclient.connect(props);
-            tracing::info!("connect: {}", r);
+            tracing::info!("connect: {:?}", r);
 
             solclient.subscribe_ext(
                 "TIC/v1/STK/*/TSE/2230",
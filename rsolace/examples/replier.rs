@@ -64,7 +64,7 @@ fn main() {
                 .compression_level(0);
 
             let r = solclient.connect(props);
-            tracing::info!("connect: {}", r);
+            tracing::info!("connect: {:?}", r);
 
             solclient.subscribe_ext("api/v1/test", SolClientSubscribeFlags::RequestConfirm);
             std::thread::sleep(std::time::Duration::from_secs(30));
@@ -53,7 +53,7 @@ fn main() {
                 .connect_timeout_ms(3000)
                 .compression_level(0);
             let r = solclient.connect(props);
-            tracing::info!("connect: {}", r);
+            tracing::info!("connect: {:?}", r);
 
             let mut msg = SolMsg::new().unwrap();
             msg.set_topic("api/v1/test");
@@ -0,0 +1,41 @@
+use dotenvy::dotenv;
+use rsolace::solclient::SolClient;
+use rsolace::solmsg::SolMsg;
+use rsolace::solresponder::{HandlerError, Reply, Responder};
+use rsolace::types::SolClientLogLevel;
+use rsolace::SessionProps;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv().ok();
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .init();
+
+    let mut solclient = SolClient::new(SolClientLogLevel::Notice)?;
+    let props = SessionProps::default()
+        .host(&std::env::var("SOLACE_HOST").unwrap_or("localhost:55555".to_string()))
+        .vpn(&std::env::var("SOLACE_VPN").unwrap_or("default".to_string()))
+        .username(&std::env::var("SOLACE_USERNAME").unwrap_or("".to_string()))
+        .password(&std::env::var("SOLACE_PASSWORD").unwrap_or("".to_string()))
+        .client_name("responder");
+    solclient.connect_async(props).await?;
+
+    let solclient = Arc::new(solclient);
+    Responder::new(solclient)
+        .max_concurrency(NonZeroUsize::new(8).unwrap())
+        .handle("api/v1/echo/>", |req: SolMsg| async move {
+            let body = req
+                .get_binary_attachment()
+                .map_err(|e| HandlerError::new("bad_request", e.to_string()))?;
+            Ok(Reply::new(body.to_vec()).with_user_prop("handler", "echo"))
+        })
+        .handle("api/v1/time", |_req: SolMsg| async move {
+            Ok::<_, HandlerError>(chrono::Utc::now().to_rfc3339())
+        })
+        .run()
+        .await?;
+    Ok(())
+}
@@ -1,9 +1,33 @@
+#[cfg(feature = "channel")]
+pub mod solbrowser;
+#[cfg(feature = "channel")]
+pub mod solbus;
 pub mod solcache;
 pub mod solclient;
+pub mod solconfig;
 pub mod solcontainer;
+#[cfg(feature = "channel")]
+pub mod soldispatch;
+pub mod solendpoint;
 pub mod solevent;
+#[cfg(feature = "channel")]
+pub mod solflow;
 pub mod solmsg;
 pub mod solprops;
+#[cfg(feature = "channel")]
+pub mod solpublisher;
+#[cfg(feature = "channel")]
+pub mod solredelivery;
+#[cfg(all(feature = "channel", feature = "tokio"))]
+pub mod solreplystream;
+#[cfg(all(feature = "channel", feature = "tokio"))]
+pub mod solresponder;
+pub mod solselector;
+pub mod solsubscription;
+#[cfg(feature = "channel")]
+pub mod soltemporary;
+#[cfg(feature = "channel")]
+pub mod soltransaction;
 pub mod types;
 pub mod utils;
 
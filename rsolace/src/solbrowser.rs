@@ -0,0 +1,105 @@
+//! Non-destructive queue inspection.
+//!
+//! A [`QueueBrowser`] binds a browser flow to a queue: the broker delivers
+//! copies of the spooled messages without removing them, so the queue's
+//! consumers are not affected. Individual messages can still be removed
+//! with [`QueueBrowser::delete`].
+
+use super::solflow::{FlowAckMode, FlowProps, SolFlow, SolFlowError};
+use super::solmsg::SolMsg;
+use kanal::Receiver;
+use std::time::Duration;
+
+/// Iterates over the messages spooled on a queue without consuming them.
+///
+/// Created with [`crate::solclient::SolClient::create_queue_browser`].
+/// Iteration ends once no message arrived for the browser's `wait`
+/// duration, which usually means the end of the queue was reached.
+///
+/// ```ignore
+/// let browser = client.create_queue_browser("orders/in", Duration::from_secs(1))?;
+/// for msg in browser.iter() {
+///     if is_stuck(&msg) {
+///         browser.delete(&msg)?;
+///     }
+/// }
+/// ```
+pub struct QueueBrowser {
+    flow: SolFlow,
+    receiver: Receiver<SolMsg>,
+    wait: Duration,
+}
+
+impl QueueBrowser {
+    /// Flow properties for browsing `queue`. Client ack mode keeps the API
+    /// from acknowledging, and so deleting, every browsed message.
+    pub fn props(queue: &str) -> FlowProps {
+        FlowProps::default()
+            .queue(queue)
+            .browser(true)
+            .ack_mode(FlowAckMode::Client)
+    }
+
+    pub(crate) fn new(flow: SolFlow, wait: Duration) -> Self {
+        let receiver = flow.get_msg_receiver();
+        Self {
+            flow,
+            receiver,
+            wait,
+        }
+    }
+
+    /// Next message on the queue, or `None` when nothing arrived within
+    /// `wait`.
+    pub fn next_msg(&self) -> Option<SolMsg> {
+        self.receiver.recv_timeout(self.wait).ok()
+    }
+
+    /// Async variant of [`QueueBrowser::next_msg`].
+    #[cfg(feature = "tokio")]
+    pub async fn next_msg_async(&self) -> Option<SolMsg> {
+        tokio::time::timeout(self.wait, self.receiver.as_async().recv())
+            .await
+            .ok()?
+            .ok()
+    }
+
+    /// Iterate over the remaining messages, see [`QueueBrowser::next_msg`].
+    pub fn iter(&self) -> impl Iterator<Item = SolMsg> + '_ {
+        std::iter::from_fn(move || self.next_msg())
+    }
+
+    /// Remove `msg`, which must have been received from this browser, from
+    /// the queue.
+    pub fn delete(&self, msg: &SolMsg) -> Result<(), SolFlowError> {
+        self.flow.ack(msg)
+    }
+
+    pub fn wait(&self) -> Duration {
+        self.wait
+    }
+
+    pub fn get_flow(&self) -> &SolFlow {
+        &self.flow
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solclient::SolClient;
+    use crate::types::SolClientLogLevel;
+
+    #[test]
+    fn test_browser_props() {
+        // browser and ack mode both set on top of the queue defaults
+        assert_eq!(QueueBrowser::props("orders/in").to_c().len(), 19);
+    }
+
+    #[test]
+    fn test_create_queue_browser_without_session() {
+        let client = SolClient::new(SolClientLogLevel::Notice).unwrap();
+        let res = client.create_queue_browser("orders/in", Duration::from_millis(10));
+        assert_eq!(res.err(), Some(SolFlowError::SessionNotConnected));
+    }
+}
@@ -0,0 +1,202 @@
+//! Fan-out of session events and messages to any number of subscribers.
+//!
+//! The receivers returned by `SolClient::get_event_receiver` and friends
+//! share one channel: every item goes to exactly one of them. A [`Bus`]
+//! instead hands every subscriber its own copy through its own bounded
+//! buffer. A subscriber that does not keep up loses the newest items and
+//! can see how many through [`BusReceiver::dropped`]; it never slows down
+//! the other subscribers or the context thread.
+//!
+//! The shared channels only queue items once one of their receivers was
+//! handed out, so a client consuming through buses alone keeps them empty.
+
+use super::solconfig::SlowConsumerEvent;
+use super::solevent::SolEvent;
+use super::solmsg::SolMsg;
+use kanal::{bounded, ReceiveError, ReceiveErrorTimeout, Receiver, Sender};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Items a [`Bus`] can hand to several subscribers.
+pub trait FanOut: Sized {
+    /// Copy for one subscriber, `None` when no copy could be made.
+    fn fan_out(&self) -> Option<Self>;
+}
+
+impl FanOut for SolEvent {
+    fn fan_out(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+impl FanOut for SlowConsumerEvent {
+    fn fan_out(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+impl FanOut for SolMsg {
+    fn fan_out(&self) -> Option<Self> {
+        self.dup().ok()
+    }
+}
+
+struct BusSubscriber<T> {
+    sender: Sender<T>,
+    dropped: Arc<AtomicU64>,
+}
+
+/// Delivers a copy of every published item to each subscriber.
+pub struct Bus<T> {
+    subscribers: Mutex<Vec<BusSubscriber<T>>>,
+}
+
+impl<T: FanOut> Bus<T> {
+    pub fn new() -> Self {
+        Self {
+            subscribers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Subscribe to every item published from now on, buffering up to
+    /// `capacity` of them.
+    pub fn subscribe(&self, capacity: NonZeroUsize) -> BusReceiver<T> {
+        let (sender, receiver) = bounded(capacity.get());
+        let dropped = Arc::new(AtomicU64::new(0));
+        self.subscribers.lock().unwrap().push(BusSubscriber {
+            sender,
+            dropped: dropped.clone(),
+        });
+        BusReceiver { receiver, dropped }
+    }
+
+    /// Hand a copy of `item` to every subscriber without blocking. Items
+    /// that don't fit a subscriber's buffer are counted as dropped for it;
+    /// subscribers whose receiver was dropped are removed.
+    pub fn publish(&self, item: &T) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|subscriber| {
+            let Some(copy) = item.fan_out() else {
+                subscriber.dropped.fetch_add(1, Ordering::Relaxed);
+                return true;
+            };
+            match subscriber.sender.try_send(copy) {
+                Ok(true) => true,
+                Ok(false) => {
+                    subscriber.dropped.fetch_add(1, Ordering::Relaxed);
+                    true
+                }
+                Err(_) => false,
+            }
+        });
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().unwrap().len()
+    }
+}
+
+impl<T: FanOut> Default for Bus<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One subscription to a [`Bus`]. Dropping it unsubscribes.
+pub struct BusReceiver<T> {
+    receiver: Receiver<T>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl<T> BusReceiver<T> {
+    pub fn recv(&self) -> Result<T, ReceiveError> {
+        self.receiver.recv()
+    }
+
+    pub fn try_recv(&self) -> Result<Option<T>, ReceiveError> {
+        self.receiver.try_recv()
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, ReceiveErrorTimeout> {
+        self.receiver.recv_timeout(timeout)
+    }
+
+    #[cfg(feature = "tokio")]
+    pub async fn recv_async(&self) -> Result<T, ReceiveError> {
+        self.receiver.as_async().recv().await
+    }
+
+    /// Items lost since subscribing because the buffer was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Items lost since the last call, resetting the count. A non-zero
+    /// result means the subscriber lagged and missed items.
+    pub fn take_dropped(&self) -> u64 {
+        self.dropped.swap(0, Ordering::Relaxed)
+    }
+
+    /// Items waiting in the buffer.
+    pub fn len(&self) -> usize {
+        self.receiver.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.receiver.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.receiver.capacity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SolClientSessionEvent;
+
+    fn cap(capacity: usize) -> NonZeroUsize {
+        NonZeroUsize::new(capacity).unwrap()
+    }
+
+    fn event() -> SolEvent {
+        SolEvent::new(SolClientSessionEvent::UpNotice, 0, "up")
+    }
+
+    #[test]
+    fn test_bus_every_subscriber_gets_every_item() {
+        let bus = Bus::new();
+        let first = bus.subscribe(cap(4));
+        let second = bus.subscribe(cap(4));
+        bus.publish(&event());
+        bus.publish(&event());
+        assert_eq!(first.len(), 2);
+        assert_eq!(second.len(), 2);
+        assert_eq!(
+            first.recv().unwrap().session_event,
+            SolClientSessionEvent::UpNotice
+        );
+    }
+
+    #[test]
+    fn test_bus_lagging_subscriber_drops_newest() {
+        let bus = Bus::new();
+        let slow = bus.subscribe(cap(1));
+        let fast = bus.subscribe(cap(8));
+        for _ in 0..3 {
+            bus.publish(&event());
+        }
+        assert_eq!(slow.len(), 1);
+        assert_eq!(slow.take_dropped(), 2);
+        assert_eq!(slow.dropped(), 0);
+        assert_eq!(fast.len(), 3);
+        assert_eq!(fast.dropped(), 0);
+
+        drop(slow);
+        bus.publish(&event());
+        assert_eq!(bus.subscriber_count(), 1);
+    }
+}
@@ -1,12 +1,41 @@
+#[cfg(feature = "channel")]
+use super::solbrowser::QueueBrowser;
+#[cfg(feature = "channel")]
+use super::solbus::{Bus, BusReceiver};
 use super::solcache::CacheSessionProps;
+pub use super::solconfig::SolClientConfig;
+#[cfg(feature = "channel")]
+use super::solconfig::{DroppedCounts, ReceiveChannel, SlowConsumerEvent};
+#[cfg(feature = "channel")]
+use super::soldispatch::{DispatchRegistry, TopicDispatch, TopicReceiver};
+use super::solendpoint::EndpointProps;
 use super::solevent::SolEvent;
+#[cfg(feature = "channel")]
+use super::solflow::{FlowProps, SolFlow, SolFlowError};
 use super::solmsg::{SolMsg, SolMsgError};
+#[cfg(feature = "channel")]
+use super::solpublisher::InFlightPermit;
+#[cfg(all(feature = "channel", feature = "tokio"))]
+use super::solreplystream::{is_end_of_stream, GatheredReply, ReplyStream, StreamReplyMap};
+#[cfg(feature = "channel")]
+use super::solsubscription::match_event_topic;
+#[cfg(all(feature = "channel", feature = "tokio"))]
+use super::solsubscription::SubscriptionError;
+use super::solsubscription::{Subscription, SubscriptionRegistry, SubscriptionStatus};
+#[cfg(feature = "channel")]
+use super::soltemporary::{TemporaryError, TemporaryQueue, TemporaryTopic};
+#[cfg(feature = "channel")]
+use super::soltransaction::{TransactedSession, TransactedSessionError, TransactedSessionProps};
 // Re-export for backward compatibility
 pub use super::solprops::SessionProps;
+#[cfg(feature = "channel")]
+use super::types::SolClientDeliveryMode;
 use super::types::{
     ErrorInfo, SolClientCacheRequestFlags, SolClientLogLevel, SolClientReturnCode,
-    SolClientSessionEvent, SolClientSubscribeFlags,
+    SolClientSessionEvent, SolClientSubCode, SolClientSubCodeOrRaw, SolClientSubscribeFlags,
 };
+#[cfg(feature = "channel")]
+use super::utils::generate_uuid;
 use super::utils::ConvertToCString;
 use dashmap::DashMap;
 use enum_primitive::FromPrimitive;
@@ -14,19 +43,27 @@ use snafu::prelude::{ensure, Snafu};
 use snafu::ResultExt;
 // use std::borrow::Cow;
 use std::ffi::{c_void, CString};
+#[cfg(feature = "channel")]
+use std::num::NonZeroUsize;
 use std::option::Option;
 use std::os::raw::c_char;
 use std::pin::Pin;
 use std::ptr::{null, null_mut};
 use std::time::Duration;
+#[cfg(feature = "channel")]
+use std::time::Instant;
 // TODO fn pointer to struct
 #[cfg(feature = "channel")]
-use kanal::{bounded, unbounded, Receiver, Sender};
+use kanal::{bounded, Receiver, Sender};
 // Async kanal imports for future async support
 #[cfg(all(feature = "channel", feature = "tokio"))]
 use kanal::{bounded_async, AsyncReceiver, AsyncSender};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
 use std::sync::Arc;
+#[cfg(feature = "channel")]
+use std::sync::Mutex;
+#[cfg(feature = "tokio")]
+use tokio::sync::watch;
 
 /// Maximum time `modify_client_info` will wait for the asynchronous
 /// `SOLCLIENT_SESSION_EVENT_MODIFYPROP_OK` / `_FAIL` confirmation.
@@ -34,18 +71,51 @@ use std::sync::Arc;
 /// Matches Solace's documented default of
 /// `SOLCLIENT_SESSION_PROP_DEFAULT_MODIFYPROP_TIMEOUT_MS = 10000`.
 const MODIFY_CLIENT_INFO_TIMEOUT_MS: u64 = 10_000;
+
+/// Maximum time `dte_unsubscribe` waits for the `TeUnsubscribeOk` /
+/// `TeUnsubscribeError` confirmation.
+const DTE_UNSUBSCRIBE_TIMEOUT_MS: u64 = 10_000;
+
+/// Minimum time between two sweeps for expired pending requests on the
+/// send path.
+#[cfg(feature = "channel")]
+const REQUEST_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
 // #[cfg_attr(feature = "tokio", derive(Debug, Clone))]
 
 #[derive(Debug, Snafu, PartialEq)]
 pub enum SolClientError {
     #[snafu(display("SolClient context create Error"))]
     ContextCreate,
+    #[snafu(display("SolClient session create code: {code:?}, Error {error:?}"))]
+    SessionCreate {
+        code: SolClientReturnCode,
+        error: ErrorInfo,
+    },
+    #[snafu(display("SolClient connect code: {code:?}, Error {error:?}"))]
+    Connect {
+        code: SolClientReturnCode,
+        error: ErrorInfo,
+    },
+    #[snafu(display("SolClient connect failed, Error {error:?}"))]
+    ConnectFailed { error: ErrorInfo },
+    #[snafu(display("SolClient connect no UpNotice within {timeout:?}"))]
+    ConnectTimeout { timeout: Duration },
+    #[snafu(display("SolClient subscribe {topic}, code: {code:?}, Error {error:?}"))]
+    Subscribe {
+        topic: String,
+        code: SolClientReturnCode,
+        error: ErrorInfo,
+    },
     #[snafu(display("SolClient send request {topic}, code: {code:?}, Error {error:?}"))]
     SendRequest {
         topic: String,
         code: SolClientReturnCode,
         error: ErrorInfo,
     },
+    #[snafu(display("SolClient request to {topic} got no reply within {timeout:?}"))]
+    RequestTimeout { topic: String, timeout: Duration },
+    #[snafu(display("SolClient session destroyed before the reply to {topic}"))]
+    SessionDestroyed { topic: String },
     #[snafu(display("SolClient send cache request {topic}, request_id: {request_id}, code: {code:?}, Error {error:?}"))]
     SendCacheRequest {
         topic: String,
@@ -53,10 +123,227 @@ pub enum SolClientError {
         code: SolClientReturnCode,
         error: ErrorInfo,
     },
+    #[snafu(display("SolClient provision endpoint {name}, code: {code:?}, Error {error:?}"))]
+    Provision {
+        name: String,
+        code: SolClientReturnCode,
+        error: ErrorInfo,
+    },
+    #[snafu(display("SolClient provision queue {name}, but the endpoint is not a queue"))]
+    NotAQueue { name: String },
+    #[snafu(display("SolClient deprovision endpoint {name}, code: {code:?}, Error {error:?}"))]
+    Deprovision {
+        name: String,
+        code: SolClientReturnCode,
+        error: ErrorInfo,
+    },
+    #[snafu(display(
+        "SolClient endpoint {name} topic subscription {topic}, code: {code:?}, Error {error:?}"
+    ))]
+    EndpointTopicSubscription {
+        name: String,
+        topic: String,
+        code: SolClientReturnCode,
+        error: ErrorInfo,
+    },
+    #[snafu(display(
+        "SolClient unsubscribe topic endpoint {name}, code: {code:?}, Error {error:?}"
+    ))]
+    DteUnsubscribe {
+        name: String,
+        code: SolClientReturnCode,
+        error: ErrorInfo,
+    },
+    #[snafu(display("SolClient send persistent msg to {topic}, code: {code:?}, Error {error:?}"))]
+    SendPersistent {
+        topic: String,
+        code: SolClientReturnCode,
+        error: ErrorInfo,
+    },
+    #[snafu(display("SolClient persistent msg rejected by broker, Error {error:?}"))]
+    PersistentRejected { error: ErrorInfo },
+    #[snafu(display("SolClient persistent msg outcome lost, session destroyed before ack"))]
+    PersistentUnconfirmed,
+    #[snafu(display("SolClient guaranteed publisher window closed"))]
+    PublisherClosed,
     #[snafu(display("SolClient inside {}", source))]
     SolMsg { source: SolMsgError },
 }
 
+/// Pending broker outcome of a message sent with
+/// [`SolClient::send_persistent`].
+///
+/// Resolves once the session event callback sees the `Acknowledgement` or
+/// `RejectedMsgError` event carrying this message's correlation tag. Can be
+/// waited on from a blocking thread ([`PersistentReceipt::wait`]) or
+/// awaited ([`PersistentReceipt::confirmed`]).
+#[cfg(feature = "channel")]
+#[derive(Debug)]
+pub struct PersistentReceipt {
+    tag: usize,
+    receiver: Receiver<Result<(), ErrorInfo>>,
+}
+
+/// A request sent without waiting, until its reply arrives or `expires`
+/// passes.
+#[cfg(feature = "channel")]
+struct PendingRequest<S> {
+    sender: S,
+    expires: Instant,
+    /// Unique per send, tells this request apart from a later one reusing
+    /// its correlation id.
+    #[cfg(feature = "tokio")]
+    tag: usize,
+}
+
+/// Removes a pending request when dropped, i.e. when the request future
+/// finishes or is cancelled. A no-op once the reply removed it, and when
+/// a later request with the same correlation id replaced it.
+#[cfg(all(feature = "channel", feature = "tokio"))]
+struct PendingRequestGuard<'a, S> {
+    map: &'a DashMap<String, PendingRequest<S>>,
+    corrid: String,
+    tag: usize,
+}
+
+#[cfg(all(feature = "channel", feature = "tokio"))]
+impl<'a, S> PendingRequestGuard<'a, S> {
+    fn new(map: &'a DashMap<String, PendingRequest<S>>, corrid: String, tag: usize) -> Self {
+        Self { map, corrid, tag }
+    }
+}
+
+#[cfg(all(feature = "channel", feature = "tokio"))]
+impl<S> Drop for PendingRequestGuard<'_, S> {
+    fn drop(&mut self) {
+        self.map
+            .remove_if(&self.corrid, |_, pending| pending.tag == self.tag);
+    }
+}
+
+/// `subscribe_confirmed` waiters keyed by tag, with their topic.
+#[cfg(feature = "channel")]
+type SubscriptionWaiters = DashMap<usize, (String, Sender<Result<(), ErrorInfo>>)>;
+
+/// Removes a `subscribe_confirmed` waiter when dropped, i.e. when the
+/// wait finishes, times out or is cancelled.
+#[cfg(all(feature = "channel", feature = "tokio"))]
+struct SubscriptionWaiterGuard<'a> {
+    waiters: &'a SubscriptionWaiters,
+    tag: usize,
+}
+
+#[cfg(all(feature = "channel", feature = "tokio"))]
+impl Drop for SubscriptionWaiterGuard<'_> {
+    fn drop(&mut self) {
+        self.waiters.remove(&self.tag);
+    }
+}
+
+/// Registered per `send_persistent` call until the broker outcome arrives.
+#[cfg(feature = "channel")]
+struct PersistentWaiter {
+    sender: Sender<Result<(), ErrorInfo>>,
+    /// Slot in a `GuaranteedPublisher` window, released when the waiter
+    /// is dropped (outcome delivered or session destroyed).
+    permit: Option<InFlightPermit>,
+}
+
+#[cfg(feature = "channel")]
+impl PersistentReceipt {
+    /// Correlation tag attached to the message.
+    pub fn tag(&self) -> usize {
+        self.tag
+    }
+
+    /// Block until the broker acks or rejects the message.
+    pub fn wait(self) -> Result<(), SolClientError> {
+        Self::outcome(self.receiver.recv().ok())
+    }
+
+    /// Like [`PersistentReceipt::wait`], but gives up after `timeout`.
+    /// Returns the receipt back on timeout so the caller can keep waiting.
+    pub fn wait_timeout(self, timeout: Duration) -> Result<Result<(), SolClientError>, Self> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(outcome) => Ok(Self::outcome(Some(outcome))),
+            Err(kanal::ReceiveErrorTimeout::Timeout) => Err(self),
+            Err(_) => Ok(Self::outcome(None)),
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    pub async fn confirmed(self) -> Result<(), SolClientError> {
+        Self::outcome(self.receiver.as_async().recv().await.ok())
+    }
+
+    fn outcome(outcome: Option<Result<(), ErrorInfo>>) -> Result<(), SolClientError> {
+        match outcome {
+            Some(Ok(())) => Ok(()),
+            Some(Err(error)) => PersistentRejectedSnafu { error }.fail(),
+            // waiter dropped by destroy_session
+            None => PersistentUnconfirmedSnafu.fail(),
+        }
+    }
+}
+
+/// Connection state of a [`SolClient`]'s session, driven by the session
+/// events.
+///
+/// Read it with [`SolClient::session_state`] or follow it with
+/// [`SolClient::watch_session_state`]; neither consumes events from
+/// [`SolClient::get_event_receiver`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SessionState {
+    /// No session, or the session was disconnected by the application.
+    Disconnected,
+    /// `connect` was called and the session is not up yet.
+    Connecting,
+    /// `UpNotice`: the session is connected.
+    Up,
+    /// `ReconnectingNotice`: the connection was lost and the API is
+    /// reconnecting. Messages published now are buffered or fail.
+    Reconnecting,
+    /// `ReconnectedNotice`: the session is connected again after a
+    /// reconnect. Direct messages sent in between may have been lost.
+    Reconnected,
+    /// `DownError` or `ConnectFailedError`: the session is gone and will
+    /// not come back without a new `connect`.
+    Down,
+}
+
+impl SessionState {
+    /// State a session enters on `event`, `None` for events that do not
+    /// change it.
+    pub fn from_event(event: SolClientSessionEvent) -> Option<SessionState> {
+        match event {
+            SolClientSessionEvent::UpNotice => Some(SessionState::Up),
+            SolClientSessionEvent::ReconnectingNotice => Some(SessionState::Reconnecting),
+            SolClientSessionEvent::ReconnectedNotice => Some(SessionState::Reconnected),
+            SolClientSessionEvent::DownError | SolClientSessionEvent::ConnectFailedError => {
+                Some(SessionState::Down)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether messages can be sent in this state.
+    pub fn is_up(&self) -> bool {
+        matches!(self, SessionState::Up | SessionState::Reconnected)
+    }
+
+    fn from_u8(value: u8) -> SessionState {
+        match value {
+            1 => SessionState::Connecting,
+            2 => SessionState::Up,
+            3 => SessionState::Reconnecting,
+            4 => SessionState::Reconnected,
+            5 => SessionState::Down,
+            _ => SessionState::Disconnected,
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct SolClientRxCallbackInfo {
@@ -123,43 +410,247 @@ struct SolClientInner {
     // session_func_info: Option<rsolace_sys::solClient_session_createFuncInfo_t>,
     // session_func_info: Option<i32>,
     session_func_info: Option<SolClientFuncInfo>,
+    /// Liveness flag of the current session, shared with every flow created
+    /// on it. A fresh flag is installed per `connect`; `destroy_session`
+    /// clears it so flows dropped later skip `solClient_flow_destroy`
+    /// (destroying the session already destroyed them).
+    session_alive: Arc<AtomicBool>,
+    /// Current `SessionState`, stored as its `u8` discriminant.
+    state: AtomicU8,
+    /// Publishes every `SessionState` change to `watch_session_state`
+    /// subscribers.
+    #[cfg(feature = "tokio")]
+    state_sender: watch::Sender<SessionState>,
+    /// Topics subscribed through `subscribe` / `subscribe_ext`, replayed
+    /// on every new session.
+    subscriptions: SubscriptionRegistry,
+    /// Live `TopicDispatch` subscriptions, replayed on every new session.
+    #[cfg(feature = "channel")]
+    dispatches: Arc<DispatchRegistry>,
     #[cfg(feature = "raw")]
     rx_msg_callback: Option<fn(&mut Self, SolMsg)>,
     #[cfg(feature = "raw")]
     rx_event_callback: Option<fn(&mut Self, SolEvent)>,
     #[cfg(feature = "channel")]
-    msg_sender: Sender<SolMsg>,
+    msg_channel: ReceiveChannel<SolMsg>,
     #[cfg(feature = "channel")]
-    msg_receiver: Receiver<SolMsg>,
+    p2p_channel: ReceiveChannel<SolMsg>,
     #[cfg(feature = "channel")]
-    p2p_sender: Sender<SolMsg>,
+    request_channel: ReceiveChannel<SolMsg>,
     #[cfg(feature = "channel")]
-    p2p_receiver: Receiver<SolMsg>,
+    event_channel: ReceiveChannel<SolEvent>,
+    /// Fan-out counterparts of the channels above: every subscription gets
+    /// its own copy of each message / event.
     #[cfg(feature = "channel")]
-    request_sender: Sender<SolMsg>,
+    msg_bus: Bus<SolMsg>,
     #[cfg(feature = "channel")]
-    request_receiver: Receiver<SolMsg>,
+    p2p_bus: Bus<SolMsg>,
     #[cfg(feature = "channel")]
-    event_sender: Sender<SolEvent>,
+    request_bus: Bus<SolMsg>,
     #[cfg(feature = "channel")]
-    event_receiver: Receiver<SolEvent>,
+    event_bus: Bus<SolEvent>,
+    /// Overflow notices of the receive channels above.
     #[cfg(feature = "channel")]
-    // request_reply_map: Arc<DashMap<String, Sender<SolMsg>>>,
-    request_reply_map: DashMap<String, Sender<SolMsg>>,
+    slow_consumer_bus: Bus<SlowConsumerEvent>,
+    /// Requests waiting for their reply, keyed by correlation id.
+    #[cfg(feature = "channel")]
+    request_reply_map: DashMap<String, PendingRequest<Sender<SolMsg>>>,
+    #[cfg(all(feature = "channel", feature = "tokio"))]
+    async_request_reply_map: DashMap<String, PendingRequest<AsyncSender<SolMsg>>>,
+    /// Streaming and gathered requests, keyed by correlation id. Unlike the
+    /// maps above an entry takes every reply, until one marks the end of a
+    /// stream; its `ReplyStream` removes it when dropped.
     #[cfg(all(feature = "channel", feature = "tokio"))]
-    // async_request_reply_map: Arc<DashMap<String, AsyncSender<SolMsg>>>,
-    async_request_reply_map: DashMap<String, AsyncSender<SolMsg>>,
+    stream_reply_map: StreamReplyMap,
+    /// How long an entry of the maps above waits for its reply.
+    #[cfg(feature = "channel")]
+    request_expiry: Duration,
+    /// When the send path next sweeps the maps above for expired entries.
+    #[cfg(feature = "channel")]
+    next_request_sweep: Mutex<Instant>,
     /// Monotonic counter used to generate unique correlation tags for
-    /// non-blocking session-modify operations. Starts at 1 so a `null`
-    /// correlation pointer is always distinguishable from a valid tag.
+    /// everything the callbacks match back to a waiter: non-blocking
+    /// session-modify operations, persistent sends, `dte_unsubscribe` and
+    /// `subscribe_confirmed`, plus fallback correlation ids and the tags
+    /// telling pending requests and reply streams apart.
+    /// Starts at 1 so a `null` correlation pointer is always
+    /// distinguishable from a valid tag.
     #[cfg(feature = "channel")]
-    next_modify_prop_tag: AtomicUsize,
+    next_correlation_tag: AtomicUsize,
     /// One-shot waiters keyed by correlation tag for in-flight
     /// `solClient_session_modifyClientInfo` calls. The event callback
     /// resolves the matching waiter with the `ModifyPropOk` /
     /// `ModifyPropFail` outcome reported by the C API.
     #[cfg(feature = "channel")]
     modify_prop_waiters: DashMap<usize, Sender<SolClientSessionEvent>>,
+    /// One-shot waiters keyed by the correlation tag attached to messages
+    /// sent with `send_persistent`, resolved by the `Acknowledgement` /
+    /// `RejectedMsgError` events.
+    #[cfg(feature = "channel")]
+    persistent_waiters: DashMap<usize, PersistentWaiter>,
+    /// One-shot waiters keyed by correlation tag for other asynchronous
+    /// session operations (e.g. `dte_unsubscribe`). Any event carrying a
+    /// registered tag resolves its waiter with the full `SolEvent`.
+    #[cfg(feature = "channel")]
+    event_waiters: DashMap<usize, Sender<SolEvent>>,
+    /// `subscribe_confirmed` waiters keyed by tag, with their topic. The
+    /// event callback resolves every waiter of the topic a
+    /// `SubscriptionOk` / `SubscriptionError` event names.
+    #[cfg(feature = "channel")]
+    subscription_waiters: SubscriptionWaiters,
+    /// Holds at most one pending `CanSend` notification for senders that
+    /// hit `WouldBlock`.
+    #[cfg(feature = "channel")]
+    can_send_sender: Sender<()>,
+    #[cfg(feature = "channel")]
+    can_send_receiver: Receiver<()>,
+    /// Outcome of a non-blocking connect: `Ok` on `UpNotice`, the error
+    /// info of the context thread on `ConnectFailedError`. Holds at most
+    /// one outcome, stale ones are drained before connecting.
+    #[cfg(all(feature = "channel", feature = "tokio"))]
+    connect_sender: Sender<Result<(), ErrorInfo>>,
+    #[cfg(all(feature = "channel", feature = "tokio"))]
+    connect_receiver: Receiver<Result<(), ErrorInfo>>,
+}
+
+impl SolClientInner {
+    /// A pending request replying to `sender`, expiring after `expiry`.
+    #[cfg(feature = "channel")]
+    fn pending_request<S>(&self, sender: S, expiry: Duration) -> PendingRequest<S> {
+        PendingRequest {
+            sender,
+            expires: Instant::now() + expiry,
+            #[cfg(feature = "tokio")]
+            tag: self.next_correlation_tag.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    /// Forget pending requests whose reply did not arrive in time,
+    /// closing their receivers. Returns how many expired.
+    #[cfg(feature = "channel")]
+    fn expire_pending_requests(&self) -> usize {
+        let now = Instant::now();
+        *self.next_request_sweep.lock().unwrap() = now + REQUEST_SWEEP_INTERVAL;
+        let before = self.request_reply_map.len();
+        self.request_reply_map
+            .retain(|_corrid, pending| pending.expires > now);
+        #[allow(unused_mut)]
+        let mut expired = before - self.request_reply_map.len();
+        #[cfg(feature = "tokio")]
+        {
+            let before = self.async_request_reply_map.len();
+            self.async_request_reply_map
+                .retain(|_corrid, pending| pending.expires > now);
+            expired += before - self.async_request_reply_map.len();
+        }
+        if expired > 0 {
+            tracing::debug!("{} pending requests expired without reply", expired);
+        }
+        expired
+    }
+
+    /// [`SolClientInner::expire_pending_requests`] at most once per
+    /// `REQUEST_SWEEP_INTERVAL`.
+    #[cfg(feature = "channel")]
+    fn sweep_pending_requests(&self) {
+        let due = *self.next_request_sweep.lock().unwrap() <= Instant::now();
+        if due {
+            self.expire_pending_requests();
+        }
+    }
+
+    /// Resolve the `subscribe_confirmed` waiters of the topic a
+    /// `SubscriptionOk` / `SubscriptionError` event names.
+    #[cfg(feature = "channel")]
+    fn resolve_subscription_waiters(&self, event: &SolEvent, error: Option<ErrorInfo>) {
+        if self.subscription_waiters.is_empty() {
+            return;
+        }
+        let topics: Vec<String> = self
+            .subscription_waiters
+            .iter()
+            .map(|waiter| waiter.value().0.clone())
+            .collect();
+        let Some(topic) = match_event_topic(&event.info, topics.iter().map(String::as_str)) else {
+            return;
+        };
+        let outcome = match event.session_event {
+            SolClientSessionEvent::SubscriptionOk => Ok(()),
+            _ => Err(error.unwrap_or_else(|| ErrorInfo {
+                sub_code: SolClientSubCodeOrRaw::Raw(event.response_code),
+                error_str: event.info.clone(),
+            })),
+        };
+        self.subscription_waiters
+            .retain(|_tag, (waiter_topic, sender)| {
+                if waiter_topic != topic {
+                    return true;
+                }
+                // bounded(1) per waiter, resolved once: never blocks.
+                let _ = sender.try_send(outcome.clone());
+                false
+            });
+    }
+
+    fn set_state(&self, state: SessionState) {
+        self.state.store(state as u8, Ordering::Release);
+        #[cfg(feature = "tokio")]
+        self.state_sender.send_replace(state);
+    }
+
+    /// Send a reply no blocking request waits for to the async request or
+    /// reply stream registered under `corr_id`, if any.
+    #[cfg(all(feature = "channel", feature = "tokio"))]
+    fn deliver_async_reply(&self, corr_id: &str, msg: SolMsg) {
+        if let Some((_corrid, pending)) = self.async_request_reply_map.remove(corr_id) {
+            // For async sender, we need to use try_send (non-blocking)
+            // since this callback cannot be async
+            match pending.sender.try_send(msg) {
+                Ok(_) => {
+                    tracing::debug!("resp sended corrid: {}", corr_id);
+                }
+                Err(e) => {
+                    tracing::error!("send msg to channel error: {:?}", e);
+                }
+            }
+        } else if let Some((sender, ends_on_marker, tag)) = self
+            .stream_reply_map
+            .get(corr_id)
+            .map(|entry| (entry.sender.clone(), entry.ends_on_marker, entry.tag))
+        {
+            // Keep the entry for the next replies,
+            // unless this one ends the stream.
+            let end = ends_on_marker && is_end_of_stream(&msg);
+            if let Err(e) = sender.try_send(msg) {
+                tracing::error!("send stream reply to channel error: {:?}", e);
+            }
+            if end {
+                self.stream_reply_map
+                    .remove_if(corr_id, |_, entry| entry.tag == tag);
+            }
+        }
+    }
+
+    /// Send `item` on `channel`, publishing a [`SlowConsumerEvent`] when
+    /// the channel starts overflowing. Only blocks for a full channel with
+    /// `OverflowPolicy::Block`; the notice itself never does.
+    #[cfg(feature = "channel")]
+    fn deliver<T>(&self, channel: &ReceiveChannel<T>, item: T) {
+        match channel.send(item) {
+            Ok(false) => {}
+            Ok(true) => {
+                let config = channel.config();
+                self.slow_consumer_bus.publish(&SlowConsumerEvent {
+                    channel: channel.name(),
+                    capacity: config.capacity(),
+                    overflow: config.overflow(),
+                });
+            }
+            Err(e) => {
+                tracing::error!("send to {} channel error: {}", channel.name(), e);
+            }
+        }
+    }
 }
 
 pub struct SolClient {
@@ -185,6 +676,8 @@ impl SolClient {
     /// Cleanly disconnect and destroy the current session, if any.
     /// After this call, `session_p` is null and safe to overwrite.
     fn destroy_session(&mut self) {
+        self.inner().session_alive.store(false, Ordering::Release);
+        self.inner().set_state(SessionState::Disconnected);
         unsafe {
             if !self.inner().session_p.is_null() {
                 rsolace_sys::solClient_session_disconnect(self.inner().session_p);
@@ -199,14 +692,31 @@ impl SolClient {
         self.inner().request_reply_map.clear();
         #[cfg(all(feature = "channel", feature = "tokio"))]
         self.inner().async_request_reply_map.clear();
+        #[cfg(all(feature = "channel", feature = "tokio"))]
+        self.inner().stream_reply_map.clear();
         // Drop any pending modify_client_info waiters. Dropping the senders
         // closes the channels so `recv_timeout` returns immediately instead
         // of blocking the full 10s when the session is torn down.
         #[cfg(feature = "channel")]
         self.inner().modify_prop_waiters.clear();
+        // Same for persistent sends: their receipts resolve to
+        // `PersistentUnconfirmed` as the broker can no longer ack them.
+        #[cfg(feature = "channel")]
+        self.inner().persistent_waiters.clear();
+        #[cfg(feature = "channel")]
+        self.inner().event_waiters.clear();
+        #[cfg(feature = "channel")]
+        self.inner().subscription_waiters.clear();
     }
 
     pub fn new(log_level: SolClientLogLevel) -> Result<SolClient, SolClientError> {
+        Self::with_config(SolClientConfig::default().log_level(log_level))
+    }
+
+    /// Create a client with bounded receive channels or other non-default
+    /// settings, see [`SolClientConfig`].
+    pub fn with_config(config: SolClientConfig) -> Result<SolClient, SolClientError> {
+        let log_level = config.log_level;
         let mut context_p: rsolace_sys::solClient_opaqueContext_pt = null_mut();
         let session_p: rsolace_sys::solClient_opaqueSession_pt = null_mut();
         unsafe {
@@ -244,10 +754,10 @@ impl SolClient {
                 ContextCreateSnafu
             );
 
-            let (msg_sender, msg_receiver) = unbounded();
-            let (p2p_sender, p2p_receiver) = unbounded();
-            let (request_sender, request_receiver) = unbounded();
-            let (event_sender, envent_receiver) = unbounded();
+            #[cfg(feature = "channel")]
+            let (can_send_sender, can_send_receiver) = bounded(1);
+            #[cfg(all(feature = "channel", feature = "tokio"))]
+            let (connect_sender, connect_receiver) = bounded(1);
 
             let inner = SolClientInner {
                 // context_p: context_p as i32,
@@ -256,38 +766,65 @@ impl SolClient {
                 // session_p: session_p as i32,
                 session_p,
                 session_func_info: None,
+                session_alive: Arc::new(AtomicBool::new(false)),
+                state: AtomicU8::new(SessionState::Disconnected as u8),
+                #[cfg(feature = "tokio")]
+                state_sender: watch::channel(SessionState::Disconnected).0,
+                subscriptions: SubscriptionRegistry::new(),
+                #[cfg(feature = "channel")]
+                dispatches: Arc::new(DispatchRegistry::default()),
                 #[cfg(feature = "raw")]
                 rx_msg_callback: None,
                 #[cfg(feature = "raw")]
                 rx_event_callback: None,
                 #[cfg(feature = "channel")]
-                msg_sender,
+                msg_channel: ReceiveChannel::new("msg", config.msg_channel),
+                #[cfg(feature = "channel")]
+                p2p_channel: ReceiveChannel::new("p2p", config.p2p_channel),
                 #[cfg(feature = "channel")]
-                msg_receiver,
+                request_channel: ReceiveChannel::new("request", config.request_channel),
                 #[cfg(feature = "channel")]
-                p2p_sender,
+                event_channel: ReceiveChannel::new("event", config.event_channel),
                 #[cfg(feature = "channel")]
-                p2p_receiver,
+                msg_bus: Bus::new(),
                 #[cfg(feature = "channel")]
-                request_sender,
+                p2p_bus: Bus::new(),
                 #[cfg(feature = "channel")]
-                request_receiver,
+                request_bus: Bus::new(),
                 #[cfg(feature = "channel")]
-                event_sender,
+                event_bus: Bus::new(),
                 #[cfg(feature = "channel")]
-                event_receiver: envent_receiver,
+                slow_consumer_bus: Bus::new(),
                 #[cfg(feature = "channel")]
-                // request_reply_map: Arc::new(DashMap::new()),
                 request_reply_map: DashMap::new(),
                 #[cfg(all(feature = "channel", feature = "tokio"))]
-                // async_request_reply_map: Arc::new(DashMap::new()),
                 async_request_reply_map: DashMap::new(),
+                #[cfg(all(feature = "channel", feature = "tokio"))]
+                stream_reply_map: StreamReplyMap::default(),
+                #[cfg(feature = "channel")]
+                request_expiry: config.request_expiry,
+                #[cfg(feature = "channel")]
+                next_request_sweep: Mutex::new(Instant::now() + REQUEST_SWEEP_INTERVAL),
                 #[cfg(feature = "channel")]
                 // Start at 1: 0 would round-trip to a null pointer and be
                 // indistinguishable from "no correlation supplied".
-                next_modify_prop_tag: AtomicUsize::new(1),
+                next_correlation_tag: AtomicUsize::new(1),
                 #[cfg(feature = "channel")]
                 modify_prop_waiters: DashMap::new(),
+                #[cfg(feature = "channel")]
+                persistent_waiters: DashMap::new(),
+                #[cfg(feature = "channel")]
+                event_waiters: DashMap::new(),
+                #[cfg(feature = "channel")]
+                subscription_waiters: DashMap::new(),
+                #[cfg(feature = "channel")]
+                can_send_sender,
+                #[cfg(feature = "channel")]
+                can_send_receiver,
+                #[cfg(all(feature = "channel", feature = "tokio"))]
+                connect_sender,
+                #[cfg(all(feature = "channel", feature = "tokio"))]
+                connect_receiver,
             };
 
             Ok(SolClient {
@@ -296,13 +833,158 @@ impl SolClient {
         }
     }
 
-    pub fn connect(&mut self, props: SessionProps) -> bool {
+    /// Create a session with `props` and connect it, blocking until the
+    /// session is up or the connect failed.
+    ///
+    /// The error carries the sub code and error string of the failure,
+    /// e.g. `LoginFailure` for bad credentials or `CommunicationError` for
+    /// an unreachable host.
+    pub fn connect(&mut self, props: SessionProps) -> Result<(), SolClientError> {
+        let rt_code = self.create_session(&props, true)?;
+        if rt_code != SolClientReturnCode::Ok {
+            let error = self.get_last_error_info().unwrap();
+            self.session_failed();
+            return ConnectSnafu {
+                code: rt_code,
+                error,
+            }
+            .fail();
+        }
+        self.resubscribe();
+        Ok(())
+    }
+
+    /// Create a session with `props` and connect it without blocking the
+    /// caller.
+    ///
+    /// Resolves on the `UpNotice` session event, or fails with
+    /// [`SolClientError::ConnectFailed`] carrying the error info of the
+    /// `ConnectFailedError` event. Gives up with
+    /// [`SolClientError::ConnectTimeout`], destroying the session, once
+    /// every connect attempt allowed by `connect_timeout_ms`,
+    /// `connect_retries`, `reconnect_retry_wait_ms` and the host list has
+    /// passed without either event.
+    #[cfg(all(feature = "channel", feature = "tokio"))]
+    pub async fn connect_async(&mut self, props: SessionProps) -> Result<(), SolClientError> {
+        while self
+            .inner()
+            .connect_receiver
+            .try_recv()
+            .ok()
+            .flatten()
+            .is_some()
+        {}
+        let rt_code = self.create_session(&props, false)?;
+        match rt_code {
+            SolClientReturnCode::Ok => {
+                self.resubscribe();
+                return Ok(());
+            }
+            SolClientReturnCode::InProgress => {}
+            _ => {
+                let error = self.get_last_error_info().unwrap();
+                self.session_failed();
+                return ConnectSnafu {
+                    code: rt_code,
+                    error,
+                }
+                .fail();
+            }
+        }
+        let receiver = self.inner().connect_receiver.as_async();
+        // The sender lives in `inner`, so the channel never closes.
+        let outcome = match props.connect_wait() {
+            Some(timeout) => match tokio::time::timeout(timeout, receiver.recv()).await {
+                Ok(outcome) => outcome.unwrap(),
+                Err(_) => {
+                    self.destroy_session();
+                    return ConnectTimeoutSnafu { timeout }.fail();
+                }
+            },
+            None => receiver.recv().await.unwrap(),
+        };
+        if let Err(error) = outcome {
+            self.session_failed();
+            return ConnectFailedSnafu { error }.fail();
+        }
+        self.resubscribe();
+        Ok(())
+    }
+
+    /// Subscribe the new session to every registered topic and every live
+    /// `TopicDispatch` / `TopicReceiver` subscription. Confirmations
+    /// are requested instead of waited for, so this never blocks; they
+    /// arrive as `SubscriptionOk` / `SubscriptionError` events and update
+    /// [`SolClient::subscriptions`].
+    fn resubscribe(&self) {
+        #[cfg(feature = "channel")]
+        self.inner()
+            .dispatches
+            .resubscribe(self.inner().session_p, &self.inner().session_alive);
+        for subscription in self.inner().subscriptions.snapshot() {
+            let flag = match subscription.flag {
+                SolClientSubscribeFlags::WaitForConfirm => SolClientSubscribeFlags::RequestConfirm,
+                flag => flag,
+            };
+            let topic = CString::new(subscription.topic.as_str()).unwrap();
+            let rt_code = unsafe {
+                rsolace_sys::solClient_session_topicSubscribeExt(
+                    self.inner().session_p,
+                    flag as rsolace_sys::solClient_subscribeFlags_t,
+                    topic.as_ptr(),
+                )
+            };
+            let rt_code = SolClientReturnCode::from_i32(rt_code).unwrap();
+            let status = Self::subscription_status(rt_code, flag).unwrap_or_else(|| {
+                tracing::warn!("resubscribe {} failed: {:?}", subscription.topic, rt_code);
+                SubscriptionStatus::Failed(self.get_last_error_info().unwrap())
+            });
+            self.inner()
+                .subscriptions
+                .set_status(&subscription.topic, status);
+        }
+    }
+
+    /// Status of a subscription right after `solClient_session_topicSubscribeExt`
+    /// returned `rt_code`, `None` when it failed.
+    fn subscription_status(
+        rt_code: SolClientReturnCode,
+        flag: SolClientSubscribeFlags,
+    ) -> Option<SubscriptionStatus> {
+        match (rt_code, flag) {
+            (SolClientReturnCode::Ok, SolClientSubscribeFlags::RequestConfirm) => {
+                Some(SubscriptionStatus::Pending)
+            }
+            // Waited for the broker, or nothing for the broker to confirm.
+            (SolClientReturnCode::Ok, _) => Some(SubscriptionStatus::Confirmed),
+            (SolClientReturnCode::InProgress, _) => Some(SubscriptionStatus::Pending),
+            _ => None,
+        }
+    }
+
+    /// Replace the current session with a new one created from `props` and
+    /// start connecting it. Returns the code of `solClient_session_connect`.
+    fn create_session(
+        &mut self,
+        props: &SessionProps,
+        blocking: bool,
+    ) -> Result<SolClientReturnCode, SolClientError> {
         // Clean up any existing session before creating a new one.
         // This prevents the old session's background cleanup from
         // interfering with the new session's channels. (Issue #6)
         self.destroy_session();
+        self.inner_mut().session_alive = Arc::new(AtomicBool::new(true));
+        self.inner().set_state(SessionState::Connecting);
 
         let mut session_props = props.to_c();
+        if !blocking {
+            session_props.pop();
+            session_props.push(
+                rsolace_sys::SOLCLIENT_SESSION_PROP_CONNECT_BLOCKING.as_ptr() as *const c_char,
+            );
+            session_props.push(rsolace_sys::SOLCLIENT_PROP_DISABLE_VAL.as_ptr() as *const c_char);
+            session_props.push(null());
+        }
         let session_props_ptr: rsolace_sys::solClient_propertyArray_pt = session_props.as_mut_ptr();
 
         // Use stable pointer from Pin<Box> - this is guaranteed never to move
@@ -324,11 +1006,11 @@ impl SolClient {
                         if msg.is_reply() {
                             let corr_id = msg.get_correlation_id().unwrap();
                             tracing::debug!("resp msg corrid: {}", corr_id);
-                            if let Some((_corrid, sender)) =
+                            if let Some((_corrid, pending)) =
                                 self_ref.request_reply_map.remove(&corr_id)
                             {
                                 {
-                                    match sender.send(msg) {
+                                    match pending.sender.send(msg) {
                                         Ok(_) => {
                                             tracing::debug!("resp sended corrid: {}", corr_id);
                                         }
@@ -337,37 +1019,24 @@ impl SolClient {
                                         }
                                     }
                                 }
-                            } else if let Some((_corrid, sender)) =
-                                self_ref.async_request_reply_map.remove(&corr_id)
-                            {
-                                {
-                                    // For async sender, we need to use try_send (non-blocking)
-                                    // since this callback cannot be async
-                                    match sender.try_send(msg) {
-                                        Ok(_) => {
-                                            tracing::debug!("resp sended corrid: {}", corr_id);
-                                        }
-                                        Err(e) => {
-                                            tracing::error!("send msg to channel error: {:?}", e);
-                                        }
-                                    }
-                                }
+                            } else {
+                                #[cfg(feature = "tokio")]
+                                self_ref.deliver_async_reply(&corr_id, msg);
                             }
                         } else {
                             match msg.get_reply_to() {
                                 Ok(reply_to) => {
                                     tracing::debug!("msg reply to: {:?}", reply_to);
-                                    if let Err(e) = self_ref.request_sender.send(msg) {
-                                        tracing::error!("send request msg to channel error: {}", e);
-                                    }
+                                    self_ref.request_bus.publish(&msg);
+                                    self_ref.deliver(&self_ref.request_channel, msg);
                                 }
                                 Err(_e) => {
                                     if msg.is_p2p() {
-                                        if let Err(e) = self_ref.p2p_sender.send(msg) {
-                                            tracing::error!("send p2p msg to channel error: {}", e);
-                                        }
-                                    } else if let Err(e) = self_ref.msg_sender.send(msg) {
-                                        tracing::error!("send msg to channel error: {}", e);
+                                        self_ref.p2p_bus.publish(&msg);
+                                        self_ref.deliver(&self_ref.p2p_channel, msg);
+                                    } else {
+                                        self_ref.msg_bus.publish(&msg);
+                                        self_ref.deliver(&self_ref.msg_channel, msg);
                                     }
                                 }
                             }
@@ -398,6 +1067,30 @@ impl SolClient {
             match event {
                 Ok(event) => {
                     let self_ref: &SolClientInner = &*(user_p as *const SolClientInner);
+                    // Late events of a session the application disconnected
+                    // must not revive its state.
+                    if let Some(state) = SessionState::from_event(event.session_event) {
+                        if self_ref.state.load(Ordering::Acquire)
+                            != SessionState::Disconnected as u8
+                        {
+                            self_ref.set_state(state);
+                        }
+                    }
+                    if matches!(
+                        event.session_event,
+                        SolClientSessionEvent::SubscriptionOk
+                            | SolClientSessionEvent::SubscriptionError
+                    ) {
+                        let error =
+                            if event.session_event == SolClientSessionEvent::SubscriptionError {
+                                ErrorInfo::take_last()
+                            } else {
+                                None
+                            };
+                        self_ref.subscriptions.on_event(&event, error.clone());
+                        #[cfg(feature = "channel")]
+                        self_ref.resolve_subscription_waiters(&event, error);
+                    }
                     #[cfg(feature = "raw")]
                     {
                         if let Some(cb) = self_ref.rx_event_callback {
@@ -440,9 +1133,71 @@ impl SolClient {
                                 }
                             }
                         }
-                        if let Err(e) = self_ref.event_sender.send(event) {
-                            tracing::error!("send event to channel error: {}", e);
+                        if matches!(
+                            event.session_event,
+                            SolClientSessionEvent::Acknowledgement
+                                | SolClientSessionEvent::RejectedMsgError
+                        ) {
+                            if let Some(tag) = event.correlation_tag {
+                                if let Some((_tag, waiter)) =
+                                    self_ref.persistent_waiters.remove(&tag)
+                                {
+                                    let outcome = if event.session_event
+                                        == SolClientSessionEvent::Acknowledgement
+                                    {
+                                        Ok(())
+                                    } else {
+                                        // The last error info of the context
+                                        // thread carries the reject sub code.
+                                        Err(ErrorInfo::take_last().unwrap_or_else(|| ErrorInfo {
+                                            sub_code: SolClientSubCodeOrRaw::Raw(
+                                                event.response_code,
+                                            ),
+                                            error_str: event.info.clone(),
+                                        }))
+                                    };
+                                    if let Err(e) = waiter.sender.try_send(outcome) {
+                                        tracing::error!(
+                                            "send_persistent waiter try_send error: {:?}",
+                                            e
+                                        );
+                                    }
+                                }
+                            }
+                        }
+                        if let Some(tag) = event.correlation_tag {
+                            if let Some((_tag, sender)) = self_ref.event_waiters.remove(&tag) {
+                                if let Err(e) = sender.try_send(event.clone()) {
+                                    tracing::error!("event waiter try_send error: {:?}", e);
+                                }
+                            }
+                        }
+                        #[cfg(feature = "tokio")]
+                        if matches!(
+                            event.session_event,
+                            SolClientSessionEvent::UpNotice
+                                | SolClientSessionEvent::ConnectFailedError
+                        ) {
+                            let outcome = if event.session_event == SolClientSessionEvent::UpNotice
+                            {
+                                Ok(())
+                            } else {
+                                Err(ErrorInfo::take_last().unwrap_or_else(|| ErrorInfo {
+                                    sub_code: SolClientSubCodeOrRaw::Raw(event.response_code),
+                                    error_str: event.info.clone(),
+                                }))
+                            };
+                            // bounded(1): a pending outcome nobody waited
+                            // for is drained by the next connect_async.
+                            let _ = self_ref.connect_sender.try_send(outcome);
+                        }
+                        if event.session_event == SolClientSessionEvent::CanSend {
+                            // bounded(1): one pending wake-up is enough, a
+                            // full channel already has one.
+                            let _ = self_ref.can_send_sender.try_send(());
                         }
+                        self_ref.event_bus.publish(&event);
+                        self_ref.deliver(&self_ref.event_channel, event);
                     }
                 }
                 Err(e) => {
@@ -487,20 +1242,38 @@ impl SolClient {
         // &mut (rsolace_sys::solClient_session_createFuncInfo_t::from(self.session_func_info.unwrap()));
         let mut session_p: rsolace_sys::solClient_opaqueSession_pt = null_mut();
         unsafe {
-            rsolace_sys::solClient_session_create(
+            let rt_code = rsolace_sys::solClient_session_create(
                 session_props_ptr,
                 self.inner().context_p,
                 &mut session_p,
                 session_func_info_ptr,
                 std::mem::size_of::<rsolace_sys::solClient_session_createFuncInfo>(),
             );
+            let rt_code = SolClientReturnCode::from_i32(rt_code).unwrap();
+            if rt_code != SolClientReturnCode::Ok {
+                let error = self.get_last_error_info().unwrap();
+                self.session_failed();
+                return SessionCreateSnafu {
+                    code: rt_code,
+                    error,
+                }
+                .fail();
+            }
             // self.inner_mut().session_p = session_p as i32;
             self.inner_mut().session_p = session_p;
             let rt_code = rsolace_sys::solClient_session_connect(self.inner().session_p);
-            rt_code == (SolClientReturnCode::Ok as i32)
+            Ok(SolClientReturnCode::from_i32(rt_code).unwrap())
         }
     }
 
+    /// Mark the session created by `create_session` as failed: `Down`,
+    /// and no longer usable by flows, transacted sessions and other
+    /// handles tied to it.
+    fn session_failed(&self) {
+        self.inner().session_alive.store(false, Ordering::Release);
+        self.inner().set_state(SessionState::Down);
+    }
+
     pub fn disconnect(&self) {
         let user_p: *const c_void = self.inner() as *const _ as *const c_void;
         tracing::debug!("disconnect const user_p: {:?}", user_p);
@@ -511,6 +1284,29 @@ impl SolClient {
                 rsolace_sys::solClient_session_disconnect(self.inner().session_p);
             }
         }
+        self.inner().set_state(SessionState::Disconnected);
+    }
+
+    /// Current state of the session.
+    pub fn session_state(&self) -> SessionState {
+        SessionState::from_u8(self.inner().state.load(Ordering::Acquire))
+    }
+
+    /// Follow the session state. The receiver sees the current state and
+    /// is notified on every change; intermediate states may be skipped
+    /// when it lags behind.
+    ///
+    /// ```ignore
+    /// let mut state = client.watch_session_state();
+    /// while state.changed().await.is_ok() {
+    ///     if *state.borrow() == SessionState::Reconnected {
+    ///         resync().await;
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub fn watch_session_state(&self) -> watch::Receiver<SessionState> {
+        self.inner().state_sender.subscribe()
     }
 
     #[cfg(feature = "raw")]
@@ -523,81 +1319,224 @@ impl SolClient {
         self.inner_mut().rx_event_callback = Some(func);
     }
 
+    /// Receiver of direct messages. Messages are queued for it from the
+    /// first call of this or `get_async_msg_receiver` on, the same goes for
+    /// the other receivers and their channels.
     #[cfg(feature = "channel")]
     pub fn get_msg_receiver(&self) -> Receiver<SolMsg> {
-        self.inner().msg_receiver.clone()
+        self.inner().msg_channel.claim().clone()
     }
 
     #[cfg(feature = "channel")]
     pub fn get_request_receiver(&self) -> Receiver<SolMsg> {
-        self.inner().request_receiver.clone()
+        self.inner().request_channel.claim().clone()
     }
 
     #[cfg(feature = "channel")]
     pub fn get_p2p_receiver(&self) -> Receiver<SolMsg> {
-        self.inner().p2p_receiver.clone()
+        self.inner().p2p_channel.claim().clone()
     }
 
     #[cfg(feature = "channel")]
     pub fn get_event_receiver(&self) -> Receiver<SolEvent> {
-        self.inner().event_receiver.clone()
+        self.inner().event_channel.claim().clone()
+    }
+
+    /// Subscribe to every direct message, independently of other
+    /// subscribers and of [`SolClient::get_msg_receiver`]. Up to
+    /// `capacity` messages are buffered; newer ones are dropped and counted
+    /// while the buffer is full.
+    #[cfg(feature = "channel")]
+    pub fn get_msg_subscription(&self, capacity: NonZeroUsize) -> BusReceiver<SolMsg> {
+        self.inner().msg_bus.subscribe(capacity)
+    }
+
+    /// Like [`SolClient::get_msg_subscription`], for request messages.
+    #[cfg(feature = "channel")]
+    pub fn get_request_subscription(&self, capacity: NonZeroUsize) -> BusReceiver<SolMsg> {
+        self.inner().request_bus.subscribe(capacity)
+    }
+
+    /// Like [`SolClient::get_msg_subscription`], for p2p messages.
+    #[cfg(feature = "channel")]
+    pub fn get_p2p_subscription(&self, capacity: NonZeroUsize) -> BusReceiver<SolMsg> {
+        self.inner().p2p_bus.subscribe(capacity)
+    }
+
+    /// Subscribe to every session event, independently of other
+    /// subscribers and of [`SolClient::get_event_receiver`]. Up to
+    /// `capacity` events are buffered; newer ones are dropped and counted
+    /// while the buffer is full.
+    #[cfg(feature = "channel")]
+    pub fn get_event_subscription(&self, capacity: NonZeroUsize) -> BusReceiver<SolEvent> {
+        self.inner().event_bus.subscribe(capacity)
+    }
+
+    /// Subscribe to the [`SlowConsumerEvent`]s raised when a receive
+    /// channel starts overflowing, buffering up to `capacity` of them.
+    #[cfg(feature = "channel")]
+    pub fn get_slow_consumer_subscription(
+        &self,
+        capacity: NonZeroUsize,
+    ) -> BusReceiver<SlowConsumerEvent> {
+        self.inner().slow_consumer_bus.subscribe(capacity)
+    }
+
+    /// Messages and events dropped so far by receive channels configured
+    /// with a dropping [`crate::solconfig::OverflowPolicy`].
+    #[cfg(feature = "channel")]
+    pub fn dropped_counts(&self) -> DroppedCounts {
+        DroppedCounts {
+            msg: self.inner().msg_channel.dropped(),
+            p2p: self.inner().p2p_channel.dropped(),
+            request: self.inner().request_channel.dropped(),
+            event: self.inner().event_channel.dropped(),
+        }
     }
 
     // Async channel support with kanal
     #[cfg(all(feature = "channel", feature = "tokio"))]
     pub fn get_async_msg_receiver(&self) -> AsyncReceiver<SolMsg> {
-        self.inner().msg_receiver.as_async().clone()
+        self.inner().msg_channel.claim().as_async().clone()
     }
 
     #[cfg(all(feature = "channel", feature = "tokio"))]
     pub fn get_async_request_receiver(&self) -> AsyncReceiver<SolMsg> {
-        self.inner().request_receiver.as_async().clone()
+        self.inner().request_channel.claim().as_async().clone()
     }
 
     #[cfg(all(feature = "channel", feature = "tokio"))]
     pub fn get_async_p2p_receiver(&self) -> AsyncReceiver<SolMsg> {
-        self.inner().p2p_receiver.as_async().clone()
+        self.inner().p2p_channel.claim().as_async().clone()
     }
 
     #[cfg(all(feature = "channel", feature = "tokio"))]
     pub fn get_async_event_receiver(&self) -> AsyncReceiver<SolEvent> {
-        self.inner().event_receiver.as_async().clone()
+        self.inner().event_channel.claim().as_async().clone()
     }
 
+    /// Subscribe to `topic`, waiting for the broker to confirm. Successful
+    /// subscriptions are registered and made again on every new session
+    /// until unsubscribed.
     pub fn subscribe(&self, topic: &str) -> SolClientReturnCode {
-        let topic = CString::new(topic).unwrap();
-        unsafe {
+        let c_topic = CString::new(topic).unwrap();
+        let rt_code = unsafe {
             let rt_code = rsolace_sys::solClient_session_topicSubscribe(
                 self.inner().session_p,
-                topic.as_ptr(),
+                c_topic.as_ptr(),
             );
             SolClientReturnCode::from_i32(rt_code).unwrap()
+        };
+        self.register_subscription(topic, SolClientSubscribeFlags::WaitForConfirm, rt_code);
+        rt_code
+    }
+
+    /// Subscribe to `topic` and wait for the broker's confirmation without
+    /// blocking the caller.
+    ///
+    /// Fails with [`SubscriptionError::Refused`] when the broker refuses
+    /// the subscription; its [`SubscriptionError::sub_code`] tells why, e.g.
+    /// `SubscriptionAclDenied`, and with [`SubscriptionError::ConfirmTimeout`]
+    /// when no outcome arrived within `timeout`. Like
+    /// [`SolClient::subscribe`], the subscription is registered and made
+    /// again on every new session.
+    ///
+    /// ```ignore
+    /// match client.subscribe_confirmed("orders/>", Duration::from_secs(5)).await {
+    ///     Ok(()) => {}
+    ///     Err(e) if e.sub_code() == Some(SolClientSubCode::SubscriptionAclDenied) => {
+    ///         tracing::error!("not allowed: {}", e);
+    ///     }
+    ///     Err(e) => return Err(e.into()),
+    /// }
+    /// ```
+    #[cfg(all(feature = "channel", feature = "tokio"))]
+    pub async fn subscribe_confirmed(
+        &self,
+        topic: &str,
+        timeout: Duration,
+    ) -> Result<(), SubscriptionError> {
+        if self.inner().session_p.is_null() || !self.inner().session_alive.load(Ordering::Acquire) {
+            return Err(SubscriptionError::SessionNotConnected {
+                topic: topic.to_string(),
+            });
+        }
+        let tag = self
+            .inner()
+            .next_correlation_tag
+            .fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = bounded(1);
+        // Registered before subscribing, the confirmation may come first.
+        self.inner()
+            .subscription_waiters
+            .insert(tag, (topic.to_string(), sender));
+        let _waiter = SubscriptionWaiterGuard {
+            waiters: &self.inner().subscription_waiters,
+            tag,
+        };
+        let rt_code = self.subscribe_ext(topic, SolClientSubscribeFlags::RequestConfirm);
+        if rt_code != SolClientReturnCode::Ok && rt_code != SolClientReturnCode::InProgress {
+            return Err(SubscriptionError::Subscribe {
+                topic: topic.to_string(),
+                code: rt_code,
+                error: self.get_last_error_info().unwrap(),
+            });
+        }
+        let outcome = tokio::time::timeout(timeout, receiver.as_async().recv()).await;
+        let topic = topic.to_string();
+        match outcome {
+            Ok(Ok(Ok(()))) => Ok(()),
+            Ok(Ok(Err(error))) => Err(SubscriptionError::Refused { topic, error }),
+            Ok(Err(_)) => Err(SubscriptionError::Unconfirmed { topic }),
+            Err(_) => Err(SubscriptionError::ConfirmTimeout { topic, timeout }),
         }
     }
 
     pub fn unsubscribe(&self, topic: &str) -> SolClientReturnCode {
-        let topic = CString::new(topic).unwrap();
-        unsafe {
+        let c_topic = CString::new(topic).unwrap();
+        let rt_code = unsafe {
             let rt_code = rsolace_sys::solClient_session_topicUnsubscribe(
                 self.inner().session_p,
-                topic.as_ptr(),
+                c_topic.as_ptr(),
             );
             SolClientReturnCode::from_i32(rt_code).unwrap()
+        };
+        self.inner().subscriptions.remove(topic);
+        rt_code
+    }
+
+    fn register_subscription(
+        &self,
+        topic: &str,
+        flag: SolClientSubscribeFlags,
+        rt_code: SolClientReturnCode,
+    ) {
+        if let Some(status) = Self::subscription_status(rt_code, flag) {
+            self.inner().subscriptions.insert(topic, flag, status);
         }
     }
 
+    /// Topic subscriptions made through `subscribe` / `subscribe_ext` and
+    /// not unsubscribed since, in the order they were made, with whether
+    /// the broker confirmed them on the current session.
+    pub fn subscriptions(&self) -> Vec<Subscription> {
+        self.inner().subscriptions.snapshot()
+    }
+
     pub fn subscribe_ext(&self, topic: &str, flag: SolClientSubscribeFlags) -> SolClientReturnCode {
         let user_p: *const c_void = self.inner() as *const _ as *const c_void;
         tracing::debug!("subscribe const user_p: {:?}", user_p);
-        let topic = CString::new(topic).unwrap();
-        unsafe {
+        let c_topic = CString::new(topic).unwrap();
+        let rt_code = unsafe {
             let rt_code = rsolace_sys::solClient_session_topicSubscribeExt(
                 self.inner().session_p,
                 flag as rsolace_sys::solClient_subscribeFlags_t,
-                topic.as_ptr(),
+                c_topic.as_ptr(),
             );
             SolClientReturnCode::from_i32(rt_code).unwrap()
-        }
+        };
+        self.register_subscription(topic, flag, rt_code);
+        rt_code
     }
 
     pub fn unsubscribe_ext(
@@ -605,15 +1544,76 @@ impl SolClient {
         topic: &str,
         flag: SolClientSubscribeFlags,
     ) -> SolClientReturnCode {
-        let topic = CString::new(topic).unwrap();
-        unsafe {
+        let c_topic = CString::new(topic).unwrap();
+        let rt_code = unsafe {
             let rt_code = rsolace_sys::solClient_session_topicUnsubscribeExt(
                 self.inner().session_p,
                 flag as rsolace_sys::solClient_subscribeFlags_t,
-                topic.as_ptr(),
+                c_topic.as_ptr(),
             );
             SolClientReturnCode::from_i32(rt_code).unwrap()
-        }
+        };
+        self.inner().subscriptions.remove(topic);
+        rt_code
+    }
+
+    /// Subscribe to `topic` and hand every matching message to `handler`
+    /// instead of the msg receiver. Wildcards are matched by the API, so
+    /// one topic pattern can route to one handler.
+    ///
+    /// The handler runs on the context thread and must return quickly.
+    /// The subscription lasts until the returned handle is dropped. Blocks
+    /// until the broker confirmed the subscription: do not call from a
+    /// message or event callback.
+    #[cfg(feature = "channel")]
+    pub fn subscribe_with_handler<F>(
+        &self,
+        topic: &str,
+        handler: F,
+    ) -> Result<TopicDispatch, SolClientError>
+    where
+        F: Fn(SolMsg) + Send + Sync + 'static,
+    {
+        self.subscribe_with_handler_ext(topic, SolClientSubscribeFlags::WaitForConfirm, handler)
+    }
+
+    /// Like [`SolClient::subscribe_with_handler`] with explicit subscribe
+    /// flags, e.g. `LocalDispatchOnly` to route part of an existing broker
+    /// subscription to `handler` without subscribing again.
+    #[cfg(feature = "channel")]
+    pub fn subscribe_with_handler_ext<F>(
+        &self,
+        topic: &str,
+        flag: SolClientSubscribeFlags,
+        handler: F,
+    ) -> Result<TopicDispatch, SolClientError>
+    where
+        F: Fn(SolMsg) + Send + Sync + 'static,
+    {
+        TopicDispatch::new(
+            &self.inner().dispatches,
+            self.inner().session_p,
+            self.inner().session_alive.clone(),
+            topic,
+            flag,
+            Box::new(handler),
+        )
+    }
+
+    /// Subscribe to `topic` and receive its messages on a channel of their
+    /// own instead of the msg receiver, bounded like the msg receiver by
+    /// [`SolClientConfig::msg_channel`]. Dropping the returned receiver
+    /// removes the subscription.
+    #[cfg(feature = "channel")]
+    pub fn subscribe_to_channel(&self, topic: &str) -> Result<TopicReceiver, SolClientError> {
+        TopicReceiver::new(
+            &self.inner().dispatches,
+            self.inner().session_p,
+            self.inner().session_alive.clone(),
+            topic,
+            SolClientSubscribeFlags::WaitForConfirm,
+            *self.inner().msg_channel.config(),
+        )
     }
 
     pub fn send_msg(&self, msg: &SolMsg) -> SolClientReturnCode {
@@ -623,6 +1623,85 @@ impl SolClient {
         SolClientReturnCode::from_i32(rt_code).unwrap()
     }
 
+    /// Send a guaranteed message and track the broker's ack for it.
+    ///
+    /// A unique correlation tag is attached with
+    /// `solClient_msg_setCorrelationTagPtr`, replacing any tag already set
+    /// on `msg`. `Direct` messages are switched to `Persistent` first, since
+    /// the broker never acknowledges Direct messages.
+    ///
+    /// ```ignore
+    /// let receipt = client.send_persistent(&mut msg)?;
+    /// receipt.wait()?; // or receipt.confirmed().await?
+    /// ```
+    #[cfg(feature = "channel")]
+    pub fn send_persistent(&self, msg: &mut SolMsg) -> Result<PersistentReceipt, SolClientError> {
+        self.send_persistent_with_permit(msg, None)
+            .map_err(|(e, _permit)| e)
+    }
+
+    /// `send_persistent` that parks `permit` with the waiter until the
+    /// outcome arrives. On failure the permit is handed back so the caller
+    /// can retry (e.g. after `WouldBlock`) without releasing its slot.
+    #[cfg(feature = "channel")]
+    pub(crate) fn send_persistent_with_permit(
+        &self,
+        msg: &mut SolMsg,
+        permit: Option<InFlightPermit>,
+    ) -> Result<PersistentReceipt, (SolClientError, Option<InFlightPermit>)> {
+        let delivery_mode = match msg.get_delivery_mode() {
+            Ok(delivery_mode) => delivery_mode,
+            Err(source) => return Err((SolClientError::SolMsg { source }, permit)),
+        };
+        if delivery_mode == SolClientDeliveryMode::Direct {
+            msg.set_delivery_mode(SolClientDeliveryMode::Persistent);
+        }
+        let tag = self
+            .inner()
+            .next_correlation_tag
+            .fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = bounded(1);
+        // Register before sending: the ack may arrive on the context thread
+        // before sendMsg returns.
+        self.inner()
+            .persistent_waiters
+            .insert(tag, PersistentWaiter { sender, permit });
+        msg.set_correlation_tag(tag);
+
+        let rt_code = self.send_msg(msg);
+        if rt_code != SolClientReturnCode::Ok {
+            let error = self.get_last_error_info().unwrap();
+            let permit = self
+                .inner()
+                .persistent_waiters
+                .remove(&tag)
+                .and_then(|(_tag, mut waiter)| waiter.permit.take());
+            return Err((
+                SolClientError::SendPersistent {
+                    topic: msg.get_topic().unwrap_or_default(),
+                    code: rt_code,
+                    error,
+                },
+                permit,
+            ));
+        }
+        Ok(PersistentReceipt { tag, receiver })
+    }
+
+    /// Receiver woken by the `CanSend` session event after a send returned
+    /// `WouldBlock`. Holds at most one pending notification.
+    #[cfg(feature = "channel")]
+    pub(crate) fn get_can_send_receiver(&self) -> Receiver<()> {
+        self.inner().can_send_receiver.clone()
+    }
+
+    /// Async variant of [`SolClient::send_persistent`] that resolves once
+    /// the broker acks or rejects the message.
+    #[cfg(all(feature = "channel", feature = "tokio"))]
+    pub async fn send_persistent_async(&self, msg: &mut SolMsg) -> Result<(), SolClientError> {
+        self.send_persistent(msg)?.confirmed().await
+    }
+
     pub fn send_multiple_msg(&self, msgs: &[&SolMsg]) -> SolClientReturnCode {
         let mut arr_msg: [rsolace_sys::solClient_opaqueMsg_pt;
             rsolace_sys::SOLCLIENT_SESSION_SEND_MULTIPLE_LIMIT as usize] =
@@ -663,10 +1742,7 @@ impl SolClient {
     }
 
     pub fn get_last_error_info(&self) -> Option<ErrorInfo> {
-        let error_info_ptr = unsafe { rsolace_sys::solClient_getLastErrorInfo() };
-        let error_info = ErrorInfo::from_error_info_ptr(error_info_ptr);
-        unsafe { rsolace_sys::solClient_resetLastErrorInfo() };
-        error_info
+        ErrorInfo::take_last()
     }
 
     #[cfg(feature = "raw")]
@@ -686,6 +1762,43 @@ impl SolClient {
         Ok(SolMsg::from_ptr(reply_msg_pt).unwrap())
     }
 
+    /// Correlation id the reply to `msg` will carry. Requests without one
+    /// get a generated UUID, set on a copy of `msg` returned along with it
+    /// and sent instead, so every send of the same message gets a new id.
+    #[cfg(feature = "channel")]
+    fn request_correlation_id(
+        &self,
+        msg: &SolMsg,
+    ) -> Result<(String, Option<SolMsg>), SolClientError> {
+        if let Ok(corrid) = msg.get_correlation_id() {
+            return Ok((corrid, None));
+        }
+        let corrid = generate_uuid().unwrap_or_else(|| {
+            let tag = self
+                .inner()
+                .next_correlation_tag
+                .fetch_add(1, Ordering::Relaxed);
+            format!("rsolace-{}", tag)
+        });
+        let mut request = msg.dup().context(SolMsgSnafu)?;
+        request.set_correlation_id(&corrid);
+        Ok((corrid, Some(request)))
+    }
+
+    /// Requests still waiting for their reply, after forgetting the ones
+    /// that expired (see [`SolClientConfig::request_expiry`]).
+    #[cfg(feature = "channel")]
+    pub fn pending_requests(&self) -> usize {
+        self.inner().expire_pending_requests();
+        #[allow(unused_mut)]
+        let mut pending = self.inner().request_reply_map.len();
+        #[cfg(feature = "tokio")]
+        {
+            pending += self.inner().async_request_reply_map.len();
+        }
+        pending
+    }
+
     #[cfg(feature = "channel")]
     pub fn send_request(
         &self,
@@ -700,9 +1813,17 @@ impl SolClient {
         // );
         // let reply_msg_pt: rsolace_sys::solClient_opaqueMsg_pt = null_mut();
         if timeout == 0 {
-            let corrid = msg.get_correlation_id().unwrap_or("c0".into());
+            self.inner().sweep_pending_requests();
+            let (corrid, request) = self.request_correlation_id(msg)?;
+            let msg = request.as_ref().unwrap_or(msg);
+            let pending = self.inner().pending_request(s, self.inner().request_expiry);
+            if self
+                .inner()
+                .request_reply_map
+                .insert(corrid.clone(), pending)
+                .is_some()
             {
-                self.inner().request_reply_map.insert(corrid, s);
+                tracing::warn!("pending request with correlation id {} replaced", corrid);
             }
             // tracing::debug!("send request with channel insert to map done");
             let (rt_code, _) = self.send_request_unsafe_part(msg, timeout);
@@ -737,48 +1858,184 @@ impl SolClient {
         &self,
         msg: &SolMsg,
     ) -> Result<AsyncReceiver<SolMsg>, SolClientError> {
-        // let user_p: *const c_void = self.inner() as *const _ as *const c_void;
-        // tracing::debug!("send_request_async_receiver const user_p: {:?}", user_p);
+        let (_corrid, _tag, receiver) =
+            self.send_request_async_start(msg, self.inner().request_expiry)?;
+        Ok(receiver)
+    }
+
+    /// Send `msg` as a request without waiting, registering the receiver
+    /// of its reply under the returned correlation id and pending request
+    /// tag until `expiry` passes.
+    #[cfg(all(feature = "channel", feature = "tokio"))]
+    fn send_request_async_start(
+        &self,
+        msg: &SolMsg,
+        expiry: Duration,
+    ) -> Result<(String, usize, AsyncReceiver<SolMsg>), SolClientError> {
         // For async implementation, we'll use kanal's async bounded channel
         let (s, r) = bounded_async(1);
 
-        let corrid = msg.get_correlation_id().unwrap_or("c0".into());
-        // Store async sender directly in the map
+        self.inner().sweep_pending_requests();
+        let (corrid, request) = self.request_correlation_id(msg)?;
+        let msg = request.as_ref().unwrap_or(msg);
+        let pending = self.inner().pending_request(s, expiry);
+        let tag = pending.tag;
+        if self
+            .inner()
+            .async_request_reply_map
+            .insert(corrid.clone(), pending)
+            .is_some()
         {
-            self.inner().async_request_reply_map.insert(corrid, s);
+            tracing::warn!("pending request with correlation id {} replaced", corrid);
         }
         let (rt_code, _) = self.send_request_unsafe_part(msg, 0);
-        ensure!(
-            rt_code == SolClientReturnCode::InProgress,
-            SendRequestSnafu {
+        if rt_code != SolClientReturnCode::InProgress {
+            self.inner()
+                .async_request_reply_map
+                .remove_if(&corrid, |_, pending| pending.tag == tag);
+            return SendRequestSnafu {
                 topic: msg.get_topic().context(SolMsgSnafu)?,
                 code: rt_code,
                 error: self.get_last_error_info().unwrap(),
             }
-        );
-        Ok(r)
+            .fail();
+        }
+        Ok((corrid, tag, r))
     }
 
+    /// Why the reply channel of a request sent with `expiry` closed: the
+    /// session was destroyed, or else the request expired.
+    #[cfg(all(feature = "channel", feature = "tokio"))]
+    fn reply_lost(&self, topic: String, expiry: Duration) -> SolClientError {
+        if self.inner().session_alive.load(Ordering::Acquire) {
+            SolClientError::RequestTimeout {
+                topic,
+                timeout: expiry,
+            }
+        } else {
+            SolClientError::SessionDestroyed { topic }
+        }
+    }
+
+    /// Send `msg` as a request and await its reply.
+    ///
+    /// Waits until the reply arrives or the request expires (see
+    /// [`SolClientConfig::request_expiry`]), failing with
+    /// [`SolClientError::RequestTimeout`], or with
+    /// [`SolClientError::SessionDestroyed`] when the session goes first.
+    /// Dropping the future cancels the request: a late reply is then
+    /// discarded.
     #[cfg(all(feature = "channel", feature = "tokio"))]
     pub async fn send_request_async(&self, msg: &SolMsg) -> Result<SolMsg, SolClientError> {
         let topic = msg.get_topic().context(SolMsgSnafu)?;
-        let receiver = self.send_request_async_receiver(msg)?;
+        let expiry = self.inner().request_expiry;
+        let (corrid, tag, receiver) = self.send_request_async_start(msg, expiry)?;
+        let _pending = PendingRequestGuard::new(&self.inner().async_request_reply_map, corrid, tag);
         receiver
             .recv()
             .await
-            .map_err(|_| SolClientError::SendRequest {
-                topic: topic,
-                code: SolClientReturnCode::Fail,
-                error: self.get_last_error_info().unwrap_or_else(|| {
-                    use crate::types::{ErrorInfo, SolClientSubCodeOrRaw};
-                    ErrorInfo {
-                        sub_code: SolClientSubCodeOrRaw::Raw(
-                            SolClientReturnCode::Fail as rsolace_sys::solClient_subCode,
-                        ),
-                        error_str: "Response timeout or channel closed".to_string(),
-                    }
-                }),
-            })
+            .map_err(|_| self.reply_lost(topic, expiry))
+    }
+
+    /// Like [`SolClient::send_request_async`], but fails with
+    /// [`SolClientError::RequestTimeout`] when no reply arrived within
+    /// `timeout`, and with [`SolClientError::SessionDestroyed`] when the
+    /// session is destroyed first.
+    ///
+    /// The request is forgotten on timeout and when the future is dropped,
+    /// so cancelled requests leave nothing behind.
+    #[cfg(all(feature = "channel", feature = "tokio"))]
+    pub async fn send_request_async_with_timeout(
+        &self,
+        msg: &SolMsg,
+        timeout: Duration,
+    ) -> Result<SolMsg, SolClientError> {
+        let topic = msg.get_topic().context(SolMsgSnafu)?;
+        let (corrid, tag, receiver) = self.send_request_async_start(msg, timeout)?;
+        let _pending = PendingRequestGuard::new(&self.inner().async_request_reply_map, corrid, tag);
+        match tokio::time::timeout(timeout, receiver.recv()).await {
+            Ok(Ok(reply)) => Ok(reply),
+            Ok(Err(_)) => Err(self.reply_lost(topic, timeout)),
+            Err(_) => RequestTimeoutSnafu { topic, timeout }.fail(),
+        }
+    }
+
+    /// Send `msg` as a request answered by several replies and receive
+    /// them as a [`ReplyStream`].
+    ///
+    /// The stream ends after the reply marked with
+    /// [`crate::solreplystream::END_OF_STREAM_PROP`], or with
+    /// [`SolClientError::RequestTimeout`] when no reply arrived for
+    /// `idle_timeout`.
+    #[cfg(all(feature = "channel", feature = "tokio"))]
+    pub fn send_request_stream(
+        &self,
+        msg: &SolMsg,
+        idle_timeout: Duration,
+    ) -> Result<ReplyStream, SolClientError> {
+        self.send_request_replies(msg, idle_timeout, true)
+    }
+
+    /// Send `msg` as a request whose replies go to the returned
+    /// [`ReplyStream`], ending after a marked reply when `ends_on_marker`.
+    #[cfg(all(feature = "channel", feature = "tokio"))]
+    fn send_request_replies(
+        &self,
+        msg: &SolMsg,
+        idle_timeout: Duration,
+        ends_on_marker: bool,
+    ) -> Result<ReplyStream, SolClientError> {
+        let topic = msg.get_topic().context(SolMsgSnafu)?;
+        self.inner().sweep_pending_requests();
+        let (corrid, request) = self.request_correlation_id(msg)?;
+        let msg = request.as_ref().unwrap_or(msg);
+        let tag = self
+            .inner()
+            .next_correlation_tag
+            .fetch_add(1, Ordering::Relaxed);
+        // Registered before sending, the first reply may come first;
+        // dropped, i.e. unregistered, when sending fails.
+        let stream = ReplyStream::register(
+            topic.clone(),
+            corrid,
+            tag,
+            idle_timeout,
+            ends_on_marker,
+            self.inner().stream_reply_map.clone(),
+        );
+        let (rt_code, _) = self.send_request_unsafe_part(msg, 0);
+        ensure!(
+            rt_code == SolClientReturnCode::InProgress,
+            SendRequestSnafu {
+                topic,
+                code: rt_code,
+                error: self.get_last_error_info().unwrap(),
+            }
+        );
+        Ok(stream)
+    }
+
+    /// Send `msg` once and collect the replies of every responder, until
+    /// `max_replies` arrived or `timeout` elapsed.
+    ///
+    /// The replies share the request's correlation id and are told apart
+    /// by their [`GatheredReply::sender_id`]. Reaching the deadline returns
+    /// the replies received so far, possibly none. Replies marked as the
+    /// end of a stream are gathered like the others and end nothing.
+    /// Returns no replies, without sending, when `max_replies` is 0.
+    #[cfg(all(feature = "channel", feature = "tokio"))]
+    pub async fn send_request_gather(
+        &self,
+        msg: &SolMsg,
+        max_replies: usize,
+        timeout: Duration,
+    ) -> Result<Vec<GatheredReply>, SolClientError> {
+        if max_replies == 0 {
+            return Ok(Vec::new());
+        }
+        self.send_request_replies(msg, timeout, false)?
+            .gather(max_replies, timeout)
+            .await
     }
 
     pub fn send_cache_request(
@@ -843,6 +2100,345 @@ impl SolClient {
         Ok(())
     }
 
+    /// Create a guaranteed-messaging consumer flow on the connected session.
+    ///
+    /// Messages received on the flow are delivered to the flow's own
+    /// receivers (`SolFlow::get_msg_receiver` /
+    /// `SolFlow::get_async_msg_receiver`), not to this client's.
+    #[cfg(feature = "channel")]
+    pub fn create_flow(&self, props: FlowProps) -> Result<SolFlow, SolFlowError> {
+        SolFlow::new(
+            self.inner().session_p,
+            self.inner().session_alive.clone(),
+            &props,
+        )
+    }
+
+    /// Move `flow`, bound to a durable topic endpoint with
+    /// [`FlowProps::topic_endpoint`], to `topic`: the flow is unbound and
+    /// the endpoint bound again with the same properties and the new topic.
+    ///
+    /// The broker changes the endpoint's subscription and deletes the
+    /// messages spooled for the old topic, including those received on
+    /// `flow` but not yet acknowledged. Take the receivers of the returned
+    /// flow; those of `flow` receive nothing more. If the new bind fails,
+    /// the endpoint is left unbound.
+    ///
+    /// Fails with [`SolFlowError::NotATopicEndpoint`] for flows bound to
+    /// another kind of endpoint or created on another session, including
+    /// transacted ones.
+    #[cfg(feature = "channel")]
+    pub fn rebind_topic_endpoint(
+        &self,
+        flow: SolFlow,
+        topic: &str,
+    ) -> Result<SolFlow, SolFlowError> {
+        flow.rebind_topic(
+            self.inner().session_p,
+            self.inner().session_alive.clone(),
+            topic,
+        )
+    }
+
+    /// Browse the messages spooled on `queue` without consuming them.
+    ///
+    /// The browser's iteration ends once no message arrived for `wait`.
+    #[cfg(feature = "channel")]
+    pub fn create_queue_browser(
+        &self,
+        queue: &str,
+        wait: Duration,
+    ) -> Result<QueueBrowser, SolFlowError> {
+        let flow = self.create_flow(QueueBrowser::props(queue))?;
+        Ok(QueueBrowser::new(flow, wait))
+    }
+
+    /// Create a temporary queue bound by a flow with default properties.
+    ///
+    /// The queue is deleted by the broker when the returned handle is
+    /// dropped.
+    #[cfg(feature = "channel")]
+    pub fn create_temporary_queue(&self) -> Result<TemporaryQueue, TemporaryError> {
+        self.create_temporary_queue_with_props(FlowProps::default())
+    }
+
+    /// Create a temporary queue bound by a flow with `props`. The bind
+    /// entity set in `props` is replaced by the temporary queue.
+    #[cfg(feature = "channel")]
+    pub fn create_temporary_queue_with_props(
+        &self,
+        props: FlowProps,
+    ) -> Result<TemporaryQueue, TemporaryError> {
+        TemporaryQueue::new(
+            self.inner().session_p,
+            self.inner().session_alive.clone(),
+            props,
+        )
+    }
+
+    /// Create a temporary topic and subscribe the session to it. Messages
+    /// published to it arrive on this client's msg receiver until the
+    /// returned handle is dropped.
+    ///
+    /// Blocks until the broker confirmed the subscription: do not call
+    /// from a message or event callback.
+    #[cfg(feature = "channel")]
+    pub fn create_temporary_topic(&self) -> Result<TemporaryTopic, TemporaryError> {
+        TemporaryTopic::new(self.inner().session_p, self.inner().session_alive.clone())
+    }
+
+    /// Create a transacted session on the connected session. Drop it
+    /// before the `SolClient` reconnects or is dropped.
+    #[cfg(feature = "channel")]
+    pub fn create_transacted_session(
+        &self,
+        props: TransactedSessionProps,
+    ) -> Result<TransactedSession, TransactedSessionError> {
+        TransactedSession::new(
+            self.inner().session_p,
+            self.inner().session_alive.clone(),
+            &props,
+        )
+    }
+
+    /// Create a queue or topic endpoint on the broker and wait for the
+    /// confirmation.
+    ///
+    /// With `ensure_exists`, an endpoint of the same name that already
+    /// exists counts as success (its properties are left untouched), so
+    /// the call can be repeated on every start-up.
+    ///
+    /// Blocks until the broker responds: do not call from a message or
+    /// event callback.
+    pub fn provision_endpoint(
+        &self,
+        props: &EndpointProps,
+        ensure_exists: bool,
+    ) -> Result<(), SolClientError> {
+        let mut c_props = props.to_c();
+        let mut flags = rsolace_sys::SOLCLIENT_PROVISION_FLAGS_WAITFORCONFIRM;
+        if ensure_exists {
+            flags |= rsolace_sys::SOLCLIENT_PROVISION_FLAGS_IGNORE_EXIST_ERRORS;
+        }
+        let rt_code = unsafe {
+            rsolace_sys::solClient_session_endpointProvision(
+                c_props.as_mut_ptr(),
+                self.inner().session_p,
+                flags,
+                null_mut(),
+                null_mut(),
+                0,
+            )
+        };
+        let rt_code = SolClientReturnCode::from_i32(rt_code).unwrap();
+        if rt_code == SolClientReturnCode::Ok {
+            return Ok(());
+        }
+        let error = self.get_last_error_info().unwrap();
+        if ensure_exists
+            && error.sub_code
+                == SolClientSubCodeOrRaw::SubCode(SolClientSubCode::EndpointAlreadyExists)
+        {
+            return Ok(());
+        }
+        ProvisionSnafu {
+            name: props.name(),
+            code: rt_code,
+            error,
+        }
+        .fail()
+    }
+
+    /// Remove a queue or topic endpoint from the broker and wait for the
+    /// confirmation. Only the endpoint type and name of `props` are used.
+    pub fn deprovision_endpoint(&self, props: &EndpointProps) -> Result<(), SolClientError> {
+        let mut c_props = props.to_c_identity();
+        let rt_code = unsafe {
+            rsolace_sys::solClient_session_endpointDeprovision(
+                c_props.as_mut_ptr(),
+                self.inner().session_p,
+                rsolace_sys::SOLCLIENT_PROVISION_FLAGS_WAITFORCONFIRM,
+                null_mut(),
+            )
+        };
+        let rt_code = SolClientReturnCode::from_i32(rt_code).unwrap();
+        ensure!(
+            rt_code == SolClientReturnCode::Ok,
+            DeprovisionSnafu {
+                name: props.name(),
+                code: rt_code,
+                error: self.get_last_error_info().unwrap(),
+            }
+        );
+        Ok(())
+    }
+
+    /// [`SolClient::provision_endpoint`] for a queue built with
+    /// [`EndpointProps::queue`], [`SolClientError::NotAQueue`] otherwise.
+    pub fn provision_queue(
+        &self,
+        props: &EndpointProps,
+        ensure_exists: bool,
+    ) -> Result<(), SolClientError> {
+        ensure!(props.is_queue(), NotAQueueSnafu { name: props.name() });
+        self.provision_endpoint(props, ensure_exists)
+    }
+
+    pub fn deprovision_queue(&self, name: &str) -> Result<(), SolClientError> {
+        self.deprovision_endpoint(&EndpointProps::queue(name))
+    }
+
+    /// Add `topic` to the subscriptions of a durable queue, so messages
+    /// published to it are spooled on the queue. Waits for the broker
+    /// confirmation.
+    pub fn endpoint_topic_subscribe(
+        &self,
+        props: &EndpointProps,
+        topic: &str,
+    ) -> Result<(), SolClientError> {
+        let mut c_props = props.to_c_identity();
+        let topic_c = topic.to_cstring();
+        let rt_code = unsafe {
+            rsolace_sys::solClient_session_endpointTopicSubscribe(
+                c_props.as_mut_ptr(),
+                self.inner().session_p,
+                SolClientSubscribeFlags::WaitForConfirm as rsolace_sys::solClient_subscribeFlags_t,
+                topic_c.as_ptr(),
+                null_mut(),
+            )
+        };
+        let rt_code = SolClientReturnCode::from_i32(rt_code).unwrap();
+        ensure!(
+            rt_code == SolClientReturnCode::Ok,
+            EndpointTopicSubscriptionSnafu {
+                name: props.name(),
+                topic,
+                code: rt_code,
+                error: self.get_last_error_info().unwrap(),
+            }
+        );
+        Ok(())
+    }
+
+    /// Remove a topic added with [`SolClient::endpoint_topic_subscribe`].
+    pub fn endpoint_topic_unsubscribe(
+        &self,
+        props: &EndpointProps,
+        topic: &str,
+    ) -> Result<(), SolClientError> {
+        let mut c_props = props.to_c_identity();
+        let topic_c = topic.to_cstring();
+        let rt_code = unsafe {
+            rsolace_sys::solClient_session_endpointTopicUnsubscribe(
+                c_props.as_mut_ptr(),
+                self.inner().session_p,
+                SolClientSubscribeFlags::WaitForConfirm as rsolace_sys::solClient_subscribeFlags_t,
+                topic_c.as_ptr(),
+                null_mut(),
+            )
+        };
+        let rt_code = SolClientReturnCode::from_i32(rt_code).unwrap();
+        ensure!(
+            rt_code == SolClientReturnCode::Ok,
+            EndpointTopicSubscriptionSnafu {
+                name: props.name(),
+                topic,
+                code: rt_code,
+                error: self.get_last_error_info().unwrap(),
+            }
+        );
+        Ok(())
+    }
+
+    /// Remove the durable topic endpoint `name` (and its spooled messages)
+    /// from the broker, waiting for the `TeUnsubscribeOk` confirmation.
+    /// No flow may be bound to the endpoint.
+    ///
+    /// Blocks until the broker responds: do not call from a message or
+    /// event callback.
+    #[cfg(feature = "channel")]
+    pub fn dte_unsubscribe(&self, name: &str) -> Result<(), SolClientError> {
+        let (tag, receiver) = self.dte_unsubscribe_start(name)?;
+        let event = receiver
+            .recv_timeout(Duration::from_millis(DTE_UNSUBSCRIBE_TIMEOUT_MS))
+            .ok();
+        self.inner().event_waiters.remove(&tag);
+        Self::dte_unsubscribe_outcome(name, event)
+    }
+
+    /// Async variant of [`SolClient::dte_unsubscribe`].
+    #[cfg(all(feature = "channel", feature = "tokio"))]
+    pub async fn dte_unsubscribe_async(&self, name: &str) -> Result<(), SolClientError> {
+        let (tag, receiver) = self.dte_unsubscribe_start(name)?;
+        let event = tokio::time::timeout(
+            Duration::from_millis(DTE_UNSUBSCRIBE_TIMEOUT_MS),
+            receiver.as_async().recv(),
+        )
+        .await
+        .ok()
+        .and_then(|event| event.ok());
+        self.inner().event_waiters.remove(&tag);
+        Self::dte_unsubscribe_outcome(name, event)
+    }
+
+    #[cfg(feature = "channel")]
+    fn dte_unsubscribe_start(
+        &self,
+        name: &str,
+    ) -> Result<(usize, Receiver<SolEvent>), SolClientError> {
+        let name_c = name.to_cstring();
+        let tag = self
+            .inner()
+            .next_correlation_tag
+            .fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = bounded(1);
+        self.inner().event_waiters.insert(tag, sender);
+        let rt_code = unsafe {
+            rsolace_sys::solClient_session_dteUnsubscribe(
+                self.inner().session_p,
+                name_c.as_ptr(),
+                tag as *mut c_void,
+            )
+        };
+        let rt_code = SolClientReturnCode::from_i32(rt_code).unwrap();
+        if rt_code != SolClientReturnCode::Ok && rt_code != SolClientReturnCode::InProgress {
+            self.inner().event_waiters.remove(&tag);
+            return DteUnsubscribeSnafu {
+                name,
+                code: rt_code,
+                error: self.get_last_error_info().unwrap(),
+            }
+            .fail();
+        }
+        Ok((tag, receiver))
+    }
+
+    #[cfg(feature = "channel")]
+    fn dte_unsubscribe_outcome(name: &str, event: Option<SolEvent>) -> Result<(), SolClientError> {
+        match event {
+            Some(event) if event.session_event == SolClientSessionEvent::TeUnsubscribeOk => Ok(()),
+            Some(event) => DteUnsubscribeSnafu {
+                name,
+                code: SolClientReturnCode::Fail,
+                error: ErrorInfo {
+                    sub_code: SolClientSubCodeOrRaw::Raw(event.response_code),
+                    error_str: event.info,
+                },
+            }
+            .fail(),
+            None => DteUnsubscribeSnafu {
+                name,
+                code: SolClientReturnCode::Fail,
+                error: ErrorInfo {
+                    sub_code: SolClientSubCodeOrRaw::SubCode(SolClientSubCode::Timeout),
+                    error_str: "timed out or session destroyed waiting for TE unsubscribe"
+                        .to_string(),
+                },
+            }
+            .fail(),
+        }
+    }
+
     pub fn send_reply(&self, rx_msg: &SolMsg, reply_msg: &SolMsg) -> SolClientReturnCode {
         let rt_code = unsafe {
             rsolace_sys::solClient_session_sendReply(
@@ -939,7 +2535,7 @@ impl SolClient {
             // event callback that may fire as soon as the call returns.
             let tag = self
                 .inner()
-                .next_modify_prop_tag
+                .next_correlation_tag
                 .fetch_add(1, Ordering::Relaxed);
             let (sender, receiver) = bounded::<SolClientSessionEvent>(1);
             self.inner().modify_prop_waiters.insert(tag, sender);
@@ -1103,14 +2699,14 @@ mod tests {
 
         // Senders should still be open — channels are created in new(), not connect()
         let msg = SolMsg::new().unwrap();
-        assert!(client.inner().msg_sender.send(msg).is_ok());
+        assert!(client.inner().msg_channel.send(msg).is_ok());
 
         let event = SolEvent::new(
             crate::types::SolClientSessionEvent::UpNotice,
             0,
             "test",
         );
-        assert!(client.inner().event_sender.send(event).is_ok());
+        assert!(client.inner().event_channel.send(event).is_ok());
 
         // Receivers should get the messages we just sent
         assert!(msg_recv.try_recv().is_ok());
@@ -1121,6 +2717,56 @@ mod tests {
         drop(request_recv);
     }
 
+    #[cfg(feature = "channel")]
+    #[test]
+    fn test_overflow_raises_slow_consumer_event() {
+        use crate::solconfig::{ChannelConfig, OverflowPolicy};
+
+        let config = SolClientConfig::default().msg_channel(ChannelConfig::bounded(
+            NonZeroUsize::new(1).unwrap(),
+            OverflowPolicy::DropNewest,
+        ));
+        let client = SolClient::with_config(config).unwrap();
+        let slow = client.get_slow_consumer_subscription(NonZeroUsize::new(8).unwrap());
+        let _msgs = client.get_msg_receiver();
+        for _ in 0..3 {
+            client
+                .inner()
+                .deliver(&client.inner().msg_channel, SolMsg::new().unwrap());
+        }
+        assert_eq!(client.dropped_counts().msg, 2);
+        let event = slow.try_recv().unwrap().unwrap();
+        assert_eq!(
+            event,
+            SlowConsumerEvent {
+                channel: "msg",
+                capacity: Some(1),
+                overflow: OverflowPolicy::DropNewest,
+            }
+        );
+        // reported once per overflow
+        assert!(slow.try_recv().unwrap().is_none());
+    }
+
+    #[cfg(feature = "channel")]
+    #[test]
+    fn test_bus_only_consumer_leaves_msg_channel_empty() {
+        let client = SolClient::new(SolClientLogLevel::Notice).unwrap();
+        let inner = client.inner();
+        let bus = client.get_msg_subscription(NonZeroUsize::new(8).unwrap());
+        for _ in 0..3 {
+            let msg = SolMsg::new().unwrap();
+            inner.msg_bus.publish(&msg);
+            inner.deliver(&inner.msg_channel, msg);
+        }
+        assert_eq!(bus.len(), 3);
+        assert!(inner.msg_channel.receiver().is_empty());
+
+        let msgs = client.get_msg_receiver();
+        inner.deliver(&inner.msg_channel, SolMsg::new().unwrap());
+        assert_eq!(msgs.len(), 1);
+    }
+
     #[test]
     fn test_drop_after_disconnect() {
         let client = SolClient::new(SolClientLogLevel::Notice).unwrap();
@@ -1153,6 +2799,111 @@ mod tests {
         // No panic, no leak, no double-free
     }
 
+    #[test]
+    fn test_session_state_from_event() {
+        assert_eq!(
+            SessionState::from_event(SolClientSessionEvent::ReconnectingNotice),
+            Some(SessionState::Reconnecting)
+        );
+        assert_eq!(
+            SessionState::from_event(SolClientSessionEvent::ConnectFailedError),
+            Some(SessionState::Down)
+        );
+        assert_eq!(
+            SessionState::from_event(SolClientSessionEvent::CanSend),
+            None
+        );
+        for state in [
+            SessionState::Disconnected,
+            SessionState::Connecting,
+            SessionState::Up,
+            SessionState::Reconnecting,
+            SessionState::Reconnected,
+            SessionState::Down,
+        ] {
+            assert_eq!(SessionState::from_u8(state as u8), state);
+        }
+    }
+
+    #[test]
+    fn test_session_state_disconnected() {
+        let client = SolClient::new(SolClientLogLevel::Notice).unwrap();
+        assert_eq!(client.session_state(), SessionState::Disconnected);
+        client.inner().set_state(SessionState::Up);
+        #[cfg(feature = "tokio")]
+        let state = client.watch_session_state();
+        client.disconnect();
+        assert_eq!(client.session_state(), SessionState::Disconnected);
+        #[cfg(feature = "tokio")]
+        assert_eq!(*state.borrow(), SessionState::Disconnected);
+    }
+
+    #[test]
+    fn test_subscriptions_registry() {
+        let client = SolClient::new(SolClientLogLevel::Notice).unwrap();
+        // no session: the subscribe fails and is not remembered
+        assert_ne!(client.subscribe("orders/>"), SolClientReturnCode::Ok);
+        assert!(client.subscriptions().is_empty());
+
+        client.inner().subscriptions.insert(
+            "orders/>",
+            SolClientSubscribeFlags::WaitForConfirm,
+            SubscriptionStatus::Confirmed,
+        );
+        client.unsubscribe("orders/>");
+        assert!(client.subscriptions().is_empty());
+
+        assert_eq!(
+            SolClient::subscription_status(
+                SolClientReturnCode::Ok,
+                SolClientSubscribeFlags::RequestConfirm
+            ),
+            Some(SubscriptionStatus::Pending)
+        );
+        assert_eq!(
+            SolClient::subscription_status(
+                SolClientReturnCode::Fail,
+                SolClientSubscribeFlags::WaitForConfirm
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_subscription_waiters_resolved_by_topic() {
+        let client = SolClient::new(SolClientLogLevel::Notice).unwrap();
+        let (ok_sender, ok_receiver) = bounded(1);
+        let (denied_sender, denied_receiver) = bounded(1);
+        let waiters = &client.inner().subscription_waiters;
+        waiters.insert(1, ("orders".to_string(), ok_sender));
+        waiters.insert(2, ("orders/eu".to_string(), denied_sender));
+
+        let other = SolEvent::new(SolClientSessionEvent::SubscriptionOk, 200, "orders/us");
+        client.inner().resolve_subscription_waiters(&other, None);
+        assert_eq!(waiters.len(), 2);
+
+        let denied = SolEvent::new(SolClientSessionEvent::SubscriptionError, 403, "orders/eu");
+        client.inner().resolve_subscription_waiters(&denied, None);
+        assert!(matches!(denied_receiver.try_recv(), Ok(Some(Err(_)))));
+        assert_eq!(waiters.len(), 1);
+
+        let ok = SolEvent::new(SolClientSessionEvent::SubscriptionOk, 200, "orders");
+        client.inner().resolve_subscription_waiters(&ok, None);
+        assert_eq!(ok_receiver.try_recv(), Ok(Some(Ok(()))));
+        assert!(waiters.is_empty());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_subscription_waiter_guard_removes_waiter() {
+        let client = SolClient::new(SolClientLogLevel::Notice).unwrap();
+        let (sender, _receiver) = bounded(1);
+        let waiters = &client.inner().subscription_waiters;
+        waiters.insert(7, ("orders".to_string(), sender));
+        drop(SubscriptionWaiterGuard { waiters, tag: 7 });
+        assert!(waiters.is_empty());
+    }
+
     #[cfg(feature = "channel")]
     #[test]
     fn test_request_reply_map_cleared_on_destroy() {
@@ -1160,14 +2911,152 @@ mod tests {
 
         // Insert a dummy entry into request_reply_map
         let (s, _r) = kanal::bounded(1);
-        client
-            .inner()
-            .request_reply_map
-            .insert("test-corr-id".to_string(), s);
+        client.inner().request_reply_map.insert(
+            "test-corr-id".to_string(),
+            client.inner().pending_request(s, Duration::from_secs(60)),
+        );
         assert!(!client.inner().request_reply_map.is_empty());
 
         client.destroy_session();
 
         assert!(client.inner().request_reply_map.is_empty());
     }
+
+    #[cfg(feature = "channel")]
+    #[test]
+    fn test_pending_requests_expire() {
+        let client = SolClient::with_config(
+            SolClientConfig::default().request_expiry(Duration::from_millis(10)),
+        )
+        .unwrap();
+        let (s, r) = kanal::bounded(1);
+        client.inner().request_reply_map.insert(
+            "expired".to_string(),
+            client.inner().pending_request(s, Duration::ZERO),
+        );
+        let (s, _live) = kanal::bounded(1);
+        client.inner().request_reply_map.insert(
+            "live".to_string(),
+            client.inner().pending_request(s, Duration::from_secs(60)),
+        );
+        assert_eq!(client.pending_requests(), 1);
+        // the expired request's receiver is closed
+        assert!(r.recv().is_err());
+
+        // requests without a correlation id get a new one on every send,
+        // set on the copy that is sent
+        let msg = SolMsg::new().unwrap();
+        let (corrid, request) = client.request_correlation_id(&msg).unwrap();
+        assert_eq!(request.unwrap().get_correlation_id().unwrap(), corrid);
+        assert!(msg.get_correlation_id().is_err());
+        let (resent, _) = client.request_correlation_id(&msg).unwrap();
+        assert_ne!(corrid, resent);
+
+        // a caller's correlation id is kept
+        let mut msg = SolMsg::new().unwrap();
+        msg.set_correlation_id("req-123");
+        let (corrid, request) = client.request_correlation_id(&msg).unwrap();
+        assert_eq!(corrid, "req-123");
+        assert!(request.is_none());
+    }
+
+    #[cfg(all(feature = "channel", feature = "tokio"))]
+    #[test]
+    fn test_pending_request_guard_removes_entry() {
+        let client = SolClient::new(SolClientLogLevel::Notice).unwrap();
+        let map = &client.inner().async_request_reply_map;
+        let (s, _r) = kanal::bounded_async(1);
+        let pending = client.inner().pending_request(s, Duration::from_secs(60));
+        let guard = PendingRequestGuard::new(map, "cancelled".to_string(), pending.tag);
+        map.insert("cancelled".to_string(), pending);
+        assert_eq!(client.pending_requests(), 1);
+        drop(guard);
+        assert_eq!(client.pending_requests(), 0);
+
+        // a guard leaves a later request with the same correlation id alone
+        let (s, _r) = kanal::bounded_async(1);
+        let first = client.inner().pending_request(s, Duration::from_secs(60));
+        let stale = PendingRequestGuard::new(map, "reused".to_string(), first.tag);
+        map.insert("reused".to_string(), first);
+        let (s, _r) = kanal::bounded_async(1);
+        map.insert(
+            "reused".to_string(),
+            client.inner().pending_request(s, Duration::from_secs(60)),
+        );
+        drop(stale);
+        assert_eq!(client.pending_requests(), 1);
+    }
+
+    #[cfg(feature = "channel")]
+    #[test]
+    fn test_persistent_receipt_unconfirmed_on_destroy() {
+        let mut client = SolClient::new(SolClientLogLevel::Notice).unwrap();
+
+        let (s, r) = kanal::bounded(1);
+        client.inner().persistent_waiters.insert(
+            7,
+            PersistentWaiter {
+                sender: s,
+                permit: None,
+            },
+        );
+        let receipt = PersistentReceipt {
+            tag: 7,
+            receiver: r,
+        };
+
+        client.destroy_session();
+
+        assert!(client.inner().persistent_waiters.is_empty());
+        assert_eq!(receipt.wait(), Err(SolClientError::PersistentUnconfirmed));
+    }
+
+    #[cfg(feature = "channel")]
+    #[test]
+    fn test_dte_unsubscribe_outcome() {
+        let ok = SolEvent::new(SolClientSessionEvent::TeUnsubscribeOk, 200, "OK");
+        assert!(SolClient::dte_unsubscribe_outcome("te", Some(ok)).is_ok());
+
+        let err = SolEvent::new(SolClientSessionEvent::TeUnsubscribeError, 400, "in use");
+        match SolClient::dte_unsubscribe_outcome("te", Some(err)) {
+            Err(SolClientError::DteUnsubscribe { name, error, .. }) => {
+                assert_eq!(name, "te");
+                assert_eq!(error.error_str, "in use");
+            }
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+
+        assert!(SolClient::dte_unsubscribe_outcome("te", None).is_err());
+    }
+
+    #[test]
+    fn test_provision_queue_rejects_topic_endpoint() {
+        let client = SolClient::new(SolClientLogLevel::Notice).unwrap();
+        let props = EndpointProps::topic_endpoint("te");
+        assert_eq!(
+            client.provision_queue(&props, true),
+            Err(SolClientError::NotAQueue {
+                name: "te".to_string()
+            })
+        );
+    }
+
+    #[cfg(feature = "channel")]
+    #[test]
+    fn test_persistent_receipt_rejected() {
+        let (s, r) = kanal::bounded(1);
+        let error = ErrorInfo {
+            sub_code: SolClientSubCodeOrRaw::Raw(1),
+            error_str: "rejected".to_string(),
+        };
+        s.send(Err(error.clone())).unwrap();
+        let receipt = PersistentReceipt {
+            tag: 1,
+            receiver: r,
+        };
+        assert_eq!(
+            receipt.wait(),
+            Err(SolClientError::PersistentRejected { error })
+        );
+    }
 }
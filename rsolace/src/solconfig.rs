@@ -0,0 +1,351 @@
+//! Client-level configuration: log level, the capacity and overflow
+//! behaviour of the channels `SolClient` delivers messages and events on,
+//! and how long requests wait for their reply.
+//!
+//! The configuration itself is available in every build, the channels
+//! applying it need the `channel` feature.
+
+use super::types::SolClientLogLevel;
+#[cfg(feature = "channel")]
+use kanal::{bounded, unbounded, Receiver, SendError, Sender};
+use std::num::NonZeroUsize;
+#[cfg(feature = "channel")]
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Default of [`SolClientConfig::request_expiry`].
+pub const DEFAULT_REQUEST_EXPIRY: Duration = Duration::from_secs(60);
+
+/// What happens to a message or event that arrives while its channel is
+/// full.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Block the context thread until the consumer makes room. Nothing is
+    /// lost, but the context thread stops: every other callback of the
+    /// context waits too, including flow messages, publisher acks and the
+    /// session events `connect_async` and `subscribe_confirmed` wait for,
+    /// and the broker eventually sees the client as slow. Only for
+    /// consumers that never wait on the client themselves.
+    Block,
+    /// Drop the arriving item and log a warning once per overflow.
+    DropNewest,
+    /// Drop the oldest buffered item to make room for the arriving one and
+    /// log a warning once per overflow.
+    DropOldest,
+    /// Drop the arriving item without logging; only the dropped count of
+    /// [`crate::solclient::SolClient::dropped_counts`] records it.
+    CountAndDrop,
+}
+
+/// Capacity and overflow policy of one receive channel.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ChannelConfig {
+    capacity: Option<usize>,
+    overflow: OverflowPolicy,
+}
+
+impl ChannelConfig {
+    /// A channel without a limit, which never overflows but grows as long
+    /// as the consumer lags behind.
+    pub fn unbounded() -> Self {
+        Self {
+            capacity: None,
+            overflow: OverflowPolicy::Block,
+        }
+    }
+
+    /// A channel holding up to `capacity` items, handling more with
+    /// `overflow`.
+    pub fn bounded(capacity: NonZeroUsize, overflow: OverflowPolicy) -> Self {
+        Self {
+            capacity: Some(capacity.get()),
+            overflow,
+        }
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    pub fn overflow(&self) -> OverflowPolicy {
+        self.overflow
+    }
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self::unbounded()
+    }
+}
+
+/// Configuration of a [`crate::solclient::SolClient`].
+///
+/// ```ignore
+/// let config = SolClientConfig::default()
+///     .log_level(SolClientLogLevel::Warning)
+///     .msg_channel(ChannelConfig::bounded(
+///         NonZeroUsize::new(100_000).unwrap(),
+///         OverflowPolicy::DropOldest,
+///     ))
+///     .event_channel(ChannelConfig::bounded(
+///         NonZeroUsize::new(1_000).unwrap(),
+///         OverflowPolicy::CountAndDrop,
+///     ));
+/// let client = SolClient::with_config(config)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct SolClientConfig {
+    pub(crate) log_level: SolClientLogLevel,
+    pub(crate) msg_channel: ChannelConfig,
+    pub(crate) p2p_channel: ChannelConfig,
+    pub(crate) request_channel: ChannelConfig,
+    pub(crate) event_channel: ChannelConfig,
+    pub(crate) request_expiry: Duration,
+}
+
+impl SolClientConfig {
+    pub fn log_level(mut self, log_level: SolClientLogLevel) -> Self {
+        self.log_level = log_level;
+        self
+    }
+
+    /// Channel behind `get_msg_receiver`.
+    pub fn msg_channel(mut self, config: ChannelConfig) -> Self {
+        self.msg_channel = config;
+        self
+    }
+
+    /// Channel behind `get_p2p_receiver`.
+    pub fn p2p_channel(mut self, config: ChannelConfig) -> Self {
+        self.p2p_channel = config;
+        self
+    }
+
+    /// Channel behind `get_request_receiver`.
+    pub fn request_channel(mut self, config: ChannelConfig) -> Self {
+        self.request_channel = config;
+        self
+    }
+
+    /// Channel behind `get_event_receiver`.
+    pub fn event_channel(mut self, config: ChannelConfig) -> Self {
+        self.event_channel = config;
+        self
+    }
+
+    /// How long a request sent without waiting (`send_request` with a
+    /// zero timeout, `send_request_async`) stays pending for its reply.
+    /// Expired requests are forgotten and their receivers closed by a sweep
+    /// that runs when a request is sent, at most once a second, and on
+    /// `SolClient::pending_requests`; until then an expired request stays
+    /// pending, however long the client goes without sending.
+    pub fn request_expiry(mut self, expiry: Duration) -> Self {
+        self.request_expiry = expiry;
+        self
+    }
+}
+
+impl Default for SolClientConfig {
+    fn default() -> Self {
+        Self {
+            log_level: SolClientLogLevel::Notice,
+            msg_channel: ChannelConfig::default(),
+            p2p_channel: ChannelConfig::default(),
+            request_channel: ChannelConfig::default(),
+            event_channel: ChannelConfig::default(),
+            request_expiry: DEFAULT_REQUEST_EXPIRY,
+        }
+    }
+}
+
+/// Raised by rsolace, not the C API, when a receive channel starts
+/// overflowing because its consumer is too slow. Delivered to
+/// `SolClient::get_slow_consumer_subscription`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowConsumerEvent {
+    /// Name of the channel, e.g. `msg` or `event`.
+    pub channel: &'static str,
+    pub capacity: Option<usize>,
+    pub overflow: OverflowPolicy,
+}
+
+/// Items dropped per receive channel since the client was created.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct DroppedCounts {
+    pub msg: u64,
+    pub p2p: u64,
+    pub request: u64,
+    pub event: u64,
+}
+
+/// A receive channel applying its [`ChannelConfig`] on every send.
+///
+/// Items are only delivered once its receiver was handed out with
+/// [`ReceiveChannel::claim`]: until then nobody could drain them, and a
+/// client consuming through bus subscriptions alone must not queue them.
+#[cfg(feature = "channel")]
+pub(crate) struct ReceiveChannel<T> {
+    name: &'static str,
+    config: ChannelConfig,
+    sender: Sender<T>,
+    receiver: Receiver<T>,
+    dropped: AtomicU64,
+    /// Set while the channel is full, so an overflow is reported once.
+    overflowing: AtomicBool,
+    /// Set once the receiver was handed out.
+    claimed: AtomicBool,
+}
+
+#[cfg(feature = "channel")]
+impl<T> ReceiveChannel<T> {
+    pub(crate) fn new(name: &'static str, config: ChannelConfig) -> Self {
+        let (sender, receiver) = match config.capacity {
+            Some(capacity) => bounded(capacity),
+            None => unbounded(),
+        };
+        Self {
+            name,
+            config,
+            sender,
+            receiver,
+            dropped: AtomicU64::new(0),
+            overflowing: AtomicBool::new(false),
+            claimed: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub(crate) fn config(&self) -> &ChannelConfig {
+        &self.config
+    }
+
+    #[cfg(test)]
+    pub(crate) fn receiver(&self) -> &Receiver<T> {
+        &self.receiver
+    }
+
+    /// The receiver, to hand out to a consumer. Items are delivered from
+    /// now on.
+    pub(crate) fn claim(&self) -> &Receiver<T> {
+        self.claimed.store(true, Ordering::Release);
+        &self.receiver
+    }
+
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Deliver `item` according to the overflow policy, or drop it while
+    /// the receiver was never claimed. Returns `true` when the channel just
+    /// started overflowing, i.e. its consumer became slow.
+    pub(crate) fn send(&self, item: T) -> Result<bool, SendError> {
+        if !self.claimed.load(Ordering::Acquire) {
+            return Ok(false);
+        }
+        let full = match self.config.overflow {
+            OverflowPolicy::Block => {
+                let full = self.sender.is_full();
+                self.sender.send(item)?;
+                full
+            }
+            OverflowPolicy::DropNewest | OverflowPolicy::CountAndDrop => {
+                let full = !self.sender.try_send(item)?;
+                if full {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                full
+            }
+            OverflowPolicy::DropOldest => {
+                let full = self.sender.is_full();
+                if full && self.receiver.try_recv().ok().flatten().is_some() {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                if !self.sender.try_send(item)? {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                full
+            }
+        };
+        if !full {
+            self.overflowing.store(false, Ordering::Relaxed);
+            return Ok(false);
+        }
+        let started = !self.overflowing.swap(true, Ordering::Relaxed);
+        if started
+            && matches!(
+                self.config.overflow,
+                OverflowPolicy::DropNewest | OverflowPolicy::DropOldest
+            )
+        {
+            tracing::warn!(
+                "{} channel full ({:?}), dropping with {:?}",
+                self.name,
+                self.config.capacity,
+                self.config.overflow
+            );
+        }
+        Ok(started)
+    }
+}
+
+#[cfg(all(test, feature = "channel"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unclaimed_channel_stays_empty() {
+        let channel = ReceiveChannel::new("msg", ChannelConfig::unbounded());
+        for i in 0..100 {
+            assert_eq!(channel.send(i), Ok(false));
+        }
+        assert!(channel.receiver().is_empty());
+        assert_eq!(channel.dropped(), 0);
+
+        let receiver = channel.claim().clone();
+        channel.send(100).unwrap();
+        assert_eq!(receiver.recv(), Ok(100));
+    }
+
+    #[test]
+    fn test_unbounded_never_overflows() {
+        let channel = ReceiveChannel::new("msg", ChannelConfig::unbounded());
+        channel.claim();
+        for i in 0..100 {
+            assert_eq!(channel.send(i), Ok(false));
+        }
+        assert_eq!(channel.receiver().len(), 100);
+        assert_eq!(channel.dropped(), 0);
+    }
+
+    #[test]
+    fn test_drop_newest_and_oldest() {
+        let newest = ReceiveChannel::new(
+            "msg",
+            ChannelConfig::bounded(NonZeroUsize::new(2).unwrap(), OverflowPolicy::CountAndDrop),
+        );
+        let oldest = ReceiveChannel::new(
+            "msg",
+            ChannelConfig::bounded(NonZeroUsize::new(2).unwrap(), OverflowPolicy::DropOldest),
+        );
+        newest.claim();
+        oldest.claim();
+        let mut started = vec![];
+        for i in 0..4 {
+            started.push(newest.send(i).unwrap());
+            oldest.send(i).unwrap();
+        }
+        // overflow reported once, on the first item that did not fit
+        assert_eq!(started, vec![false, false, true, false]);
+        assert_eq!(newest.dropped(), 2);
+        assert_eq!(newest.receiver().recv(), Ok(0));
+        assert_eq!(oldest.dropped(), 2);
+        assert_eq!(oldest.receiver().recv(), Ok(2));
+
+        // draining ends the overflow
+        newest.receiver().recv().unwrap();
+        assert_eq!(newest.send(9), Ok(false));
+    }
+}
@@ -0,0 +1,320 @@
+//! Per-subscription message dispatch.
+//!
+//! A topic subscribed with `solClient_session_topicSubscribeWithDispatch`
+//! carries its own callback: the API matches received messages against the
+//! subscription, wildcards included, and hands them to that callback
+//! instead of the session's. [`TopicDispatch`] owns such a subscription and
+//! its handler and removes both when dropped.
+//!
+//! Like the session's other subscriptions, they are lost when `connect`
+//! replaces the session, so the client keeps them in a
+//! [`DispatchRegistry`] and subscribes them again on the new session.
+
+use super::solclient::SolClientError;
+use super::solconfig::{ChannelConfig, ReceiveChannel};
+use super::solmsg::SolMsg;
+use super::types::{ErrorInfo, SolClientReturnCode, SolClientSubscribeFlags};
+use enum_primitive::FromPrimitive;
+use kanal::{ReceiveError, ReceiveErrorTimeout, Receiver};
+use std::ffi::{c_void, CString};
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+type DispatchHandler = Box<dyn Fn(SolMsg) + Send + Sync>;
+
+/// Called on the context thread for every message matching a dispatch
+/// subscription. The API keeps the message, the handler gets a copy.
+unsafe extern "C" fn dispatch_callback(
+    _opaque_session_p: rsolace_sys::solClient_opaqueSession_pt,
+    msg_p: rsolace_sys::solClient_opaqueMsg_pt,
+    user_p: *mut c_void,
+) -> rsolace_sys::solClient_rxMsgCallback_returnCode_t {
+    let handler = &*(user_p as *const DispatchHandler);
+    let mut copy_p: rsolace_sys::solClient_opaqueMsg_pt = null_mut();
+    if rsolace_sys::solClient_msg_dup(msg_p, &mut copy_p) == SolClientReturnCode::Ok as i32 {
+        match SolMsg::from_ptr(copy_p) {
+            Ok(msg) => handler(msg),
+            Err(e) => tracing::error!("dispatch msg from ptr error: {}", e),
+        }
+    } else {
+        tracing::error!("dispatch msg dup error");
+    }
+    rsolace_sys::solClient_rxMsgCallback_returnCode_SOLCLIENT_CALLBACK_OK
+}
+
+fn func_info(
+    handler: *mut DispatchHandler,
+) -> rsolace_sys::solClient_session_rxMsgDispatchFuncInfo_t {
+    rsolace_sys::solClient_session_rxMsgDispatchFuncInfo_t {
+        dispatchType: rsolace_sys::solClient_dispatchType_SOLCLIENT_DISPATCH_TYPE_CALLBACK,
+        callback_p: Some(dispatch_callback),
+        user_p: handler as *mut c_void,
+        rfu_p: null_mut(),
+    }
+}
+
+/// The session a dispatch subscription currently lives on.
+struct DispatchSession {
+    session_p: rsolace_sys::solClient_opaqueSession_pt,
+    session_alive: Arc<AtomicBool>,
+    /// Set when the handle is dropped: never subscribed again.
+    closed: bool,
+}
+
+struct DispatchEntry {
+    topic: CString,
+    flag: SolClientSubscribeFlags,
+    handler: *mut DispatchHandler,
+    session: Mutex<DispatchSession>,
+}
+
+impl DispatchEntry {
+    fn subscribe(
+        &self,
+        session_p: rsolace_sys::solClient_opaqueSession_pt,
+        flag: SolClientSubscribeFlags,
+    ) -> SolClientReturnCode {
+        let rt_code = unsafe {
+            rsolace_sys::solClient_session_topicSubscribeWithDispatch(
+                session_p,
+                flag as rsolace_sys::solClient_subscribeFlags_t,
+                self.topic.as_ptr(),
+                &mut func_info(self.handler),
+                null_mut(),
+            )
+        };
+        SolClientReturnCode::from_i32(rt_code).unwrap()
+    }
+}
+
+// SAFETY: the handler is Send + Sync and the session pointer is only used
+// to (un)subscribe, which the C API allows from any thread.
+unsafe impl Send for DispatchEntry {}
+unsafe impl Sync for DispatchEntry {}
+
+/// The live dispatch subscriptions of a client.
+#[derive(Default)]
+pub(crate) struct DispatchRegistry {
+    entries: Mutex<Vec<Arc<DispatchEntry>>>,
+}
+
+impl DispatchRegistry {
+    /// Subscribe every live dispatch subscription on a new session.
+    /// Confirmations are requested instead of waited for, as for the
+    /// client's other subscriptions.
+    pub(crate) fn resubscribe(
+        &self,
+        session_p: rsolace_sys::solClient_opaqueSession_pt,
+        session_alive: &Arc<AtomicBool>,
+    ) {
+        for entry in self.entries.lock().unwrap().iter() {
+            let mut session = entry.session.lock().unwrap();
+            if session.closed {
+                continue;
+            }
+            let flag = match entry.flag {
+                SolClientSubscribeFlags::WaitForConfirm => SolClientSubscribeFlags::RequestConfirm,
+                flag => flag,
+            };
+            let rt_code = entry.subscribe(session_p, flag);
+            if !matches!(
+                rt_code,
+                SolClientReturnCode::Ok | SolClientReturnCode::InProgress
+            ) {
+                tracing::warn!(
+                    "resubscribe dispatch topic {:?} failed: {:?}",
+                    entry.topic,
+                    rt_code
+                );
+            }
+            session.session_p = session_p;
+            session.session_alive = session_alive.clone();
+        }
+    }
+
+    fn remove(&self, entry: &Arc<DispatchEntry>) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|other| !Arc::ptr_eq(other, entry));
+    }
+}
+
+/// A topic subscription whose messages go to its own handler.
+///
+/// Created with [`crate::solclient::SolClient::subscribe_with_handler`].
+/// The handler runs on the context thread, so it must return quickly and
+/// must not call blocking `SolClient` methods. The subscription follows
+/// the client to every new session until the handle is dropped, which
+/// removes it; do not drop it from a message or event callback.
+pub struct TopicDispatch {
+    entry: Arc<DispatchEntry>,
+    registry: Arc<DispatchRegistry>,
+}
+
+impl TopicDispatch {
+    pub(crate) fn new(
+        registry: &Arc<DispatchRegistry>,
+        session_p: rsolace_sys::solClient_opaqueSession_pt,
+        session_alive: Arc<AtomicBool>,
+        topic: &str,
+        flag: SolClientSubscribeFlags,
+        handler: DispatchHandler,
+    ) -> Result<TopicDispatch, SolClientError> {
+        let entry = Arc::new(DispatchEntry {
+            topic: CString::new(topic).unwrap(),
+            flag,
+            handler: Box::into_raw(Box::new(handler)),
+            session: Mutex::new(DispatchSession {
+                session_p,
+                session_alive,
+                closed: false,
+            }),
+        });
+        let rt_code = entry.subscribe(session_p, flag);
+        if rt_code != SolClientReturnCode::Ok {
+            unsafe { drop(Box::from_raw(entry.handler)) };
+            return Err(SolClientError::Subscribe {
+                topic: topic.to_string(),
+                code: rt_code,
+                error: ErrorInfo::take_last().unwrap(),
+            });
+        }
+        registry.entries.lock().unwrap().push(entry.clone());
+        Ok(TopicDispatch {
+            entry,
+            registry: registry.clone(),
+        })
+    }
+
+    pub fn topic(&self) -> &str {
+        self.entry.topic.to_str().unwrap()
+    }
+}
+
+impl Drop for TopicDispatch {
+    fn drop(&mut self) {
+        self.registry.remove(&self.entry);
+        let mut session = self.entry.session.lock().unwrap();
+        session.closed = true;
+        // A destroyed session took its dispatch table with it.
+        if session.session_alive.load(Ordering::Acquire) {
+            // A local-only entry leaves the broker subscription to its owner.
+            let flag = match self.entry.flag {
+                SolClientSubscribeFlags::LocalDispatchOnly => self.entry.flag,
+                _ => SolClientSubscribeFlags::WaitForConfirm,
+            };
+            let rt_code = unsafe {
+                rsolace_sys::solClient_session_topicUnsubscribeWithDispatch(
+                    session.session_p,
+                    flag as rsolace_sys::solClient_subscribeFlags_t,
+                    self.entry.topic.as_ptr(),
+                    &mut func_info(self.entry.handler),
+                    null_mut(),
+                )
+            };
+            if rt_code != SolClientReturnCode::Ok as rsolace_sys::solClient_returnCode_t {
+                tracing::warn!("unsubscribe dispatch topic {:?} failed", self.entry.topic);
+            }
+        }
+        // Once unsubscribed, the API no longer calls the handler.
+        unsafe { drop(Box::from_raw(self.entry.handler)) };
+    }
+}
+
+/// The messages of one topic subscription, see
+/// [`crate::solclient::SolClient::subscribe_to_channel`].
+///
+/// The channel has the capacity and overflow policy of the client's msg
+/// channel. Dropping it removes the subscription.
+pub struct TopicReceiver {
+    dispatch: TopicDispatch,
+    receiver: Receiver<SolMsg>,
+}
+
+impl TopicReceiver {
+    pub(crate) fn new(
+        registry: &Arc<DispatchRegistry>,
+        session_p: rsolace_sys::solClient_opaqueSession_pt,
+        session_alive: Arc<AtomicBool>,
+        topic: &str,
+        flag: SolClientSubscribeFlags,
+        config: ChannelConfig,
+    ) -> Result<TopicReceiver, SolClientError> {
+        let channel = ReceiveChannel::new("dispatch", config);
+        let receiver = channel.claim().clone();
+        let handler = Box::new(move |msg| {
+            if let Err(e) = channel.send(msg) {
+                tracing::error!("send dispatch msg to channel error: {}", e);
+            }
+        });
+        let dispatch =
+            TopicDispatch::new(registry, session_p, session_alive, topic, flag, handler)?;
+        Ok(TopicReceiver { dispatch, receiver })
+    }
+
+    pub fn recv(&self) -> Result<SolMsg, ReceiveError> {
+        self.receiver.recv()
+    }
+
+    pub fn try_recv(&self) -> Result<Option<SolMsg>, ReceiveError> {
+        self.receiver.try_recv()
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<SolMsg, ReceiveErrorTimeout> {
+        self.receiver.recv_timeout(timeout)
+    }
+
+    #[cfg(feature = "tokio")]
+    pub async fn recv_async(&self) -> Result<SolMsg, ReceiveError> {
+        self.receiver.as_async().recv().await
+    }
+
+    /// The underlying receiver. It keeps working only as long as this
+    /// `TopicReceiver` is alive.
+    pub fn get_receiver(&self) -> Receiver<SolMsg> {
+        self.receiver.clone()
+    }
+
+    pub fn topic(&self) -> &str {
+        self.dispatch.topic()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solclient::SolClient;
+    use crate::types::SolClientLogLevel;
+
+    #[test]
+    fn test_subscribe_with_handler_without_session() {
+        let client = SolClient::new(SolClientLogLevel::Notice).unwrap();
+        let res = client.subscribe_with_handler("orders/>", |_| {});
+        assert!(matches!(
+            res,
+            Err(SolClientError::Subscribe { ref topic, .. }) if topic == "orders/>"
+        ));
+        assert!(client.subscribe_to_channel("orders/>").is_err());
+    }
+
+    #[test]
+    fn test_registry_skips_failed_subscriptions() {
+        let registry = Arc::new(DispatchRegistry::default());
+        let alive = Arc::new(AtomicBool::new(false));
+        let res = TopicDispatch::new(
+            &registry,
+            null_mut(),
+            alive.clone(),
+            "orders/>",
+            SolClientSubscribeFlags::WaitForConfirm,
+            Box::new(|_| {}),
+        );
+        assert!(res.is_err());
+        assert!(registry.entries.lock().unwrap().is_empty());
+        // nothing to subscribe again
+        registry.resubscribe(null_mut(), &alive);
+    }
+}
@@ -0,0 +1,317 @@
+//! Properties of broker endpoints.
+//!
+//! [`EndpointProps`] describes a durable queue or topic endpoint for
+//! `SolClient::provision_endpoint` / `provision_queue`, which create it on
+//! the broker, and for the deprovision and queue topic subscription calls,
+//! which only use its identity.
+
+use super::utils::ConvertToCString;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::ptr::null;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointAccessType {
+    /// Messages are spread over all bound consumers.
+    NonExclusive,
+    /// Only the first bound consumer receives messages, the others are
+    /// standbys.
+    Exclusive,
+}
+
+impl EndpointAccessType {
+    fn as_prop(&self) -> &'static [u8] {
+        match self {
+            EndpointAccessType::NonExclusive => {
+                rsolace_sys::SOLCLIENT_ENDPOINT_PROP_ACCESSTYPE_NONEXCLUSIVE
+            }
+            EndpointAccessType::Exclusive => {
+                rsolace_sys::SOLCLIENT_ENDPOINT_PROP_ACCESSTYPE_EXCLUSIVE
+            }
+        }
+    }
+}
+
+/// Permission granted to clients other than the endpoint owner. Each level
+/// includes the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointPermission {
+    None,
+    ReadOnly,
+    Consume,
+    ModifyTopic,
+    Delete,
+}
+
+impl EndpointPermission {
+    fn as_prop(&self) -> &'static [u8] {
+        match self {
+            EndpointPermission::None => rsolace_sys::SOLCLIENT_ENDPOINT_PERM_NONE,
+            EndpointPermission::ReadOnly => rsolace_sys::SOLCLIENT_ENDPOINT_PERM_READ_ONLY,
+            EndpointPermission::Consume => rsolace_sys::SOLCLIENT_ENDPOINT_PERM_CONSUME,
+            EndpointPermission::ModifyTopic => rsolace_sys::SOLCLIENT_ENDPOINT_PERM_MODIFY_TOPIC,
+            EndpointPermission::Delete => rsolace_sys::SOLCLIENT_ENDPOINT_PERM_DELETE,
+        }
+    }
+}
+
+/// Dead message queue handling of an endpoint.
+///
+/// The dead message queue itself is configured on the broker, the API can
+/// only provision when an endpoint moves its DMQ eligible messages there.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DmqSettings {
+    /// Deliveries after which a message is moved to the DMQ (0 = retry
+    /// forever).
+    pub max_msg_redelivery: Option<u8>,
+    /// Whether messages are moved to the DMQ when their TTL expires.
+    pub respects_msg_ttl: Option<bool>,
+}
+
+/// Properties of a queue or topic endpoint to provision on the broker.
+///
+/// Unset optional properties are left to the broker's defaults for the
+/// Message VPN.
+///
+/// ```ignore
+/// let props = EndpointProps::queue("orders/in")
+///     .access_type(Some(EndpointAccessType::Exclusive))
+///     .quota_mb(Some(100))
+///     .dmq(DmqSettings {
+///         max_msg_redelivery: Some(3),
+///         respects_msg_ttl: Some(true),
+///     });
+/// client.provision_queue(&props, true)?;
+/// ```
+#[derive(Debug)]
+pub struct EndpointProps {
+    id: &'static [u8],
+    name: CString,
+    durable: CString,
+    access_type: Option<&'static [u8]>,
+    permission: Option<&'static [u8]>,
+    quota_mb: Option<CString>,
+    max_msg_size: Option<CString>,
+    max_msg_redelivery: Option<CString>,
+    respects_msg_ttl: Option<CString>,
+    discard_behavior: Option<&'static [u8]>,
+}
+
+impl EndpointProps {
+    /// A durable queue named `name`.
+    pub fn queue(name: &str) -> Self {
+        Self::new(rsolace_sys::SOLCLIENT_ENDPOINT_PROP_QUEUE, name)
+    }
+
+    /// A durable topic endpoint named `name`.
+    pub fn topic_endpoint(name: &str) -> Self {
+        Self::new(rsolace_sys::SOLCLIENT_ENDPOINT_PROP_TE, name)
+    }
+
+    fn new(id: &'static [u8], name: &str) -> Self {
+        Self {
+            id,
+            name: name.to_cstring(),
+            durable: true.to_cstring(),
+            access_type: None,
+            permission: None,
+            quota_mb: None,
+            max_msg_size: None,
+            max_msg_redelivery: None,
+            respects_msg_ttl: None,
+            discard_behavior: None,
+        }
+    }
+
+    /// Convert to a C-compatible, null-terminated property array with
+    /// every property that is set. The returned pointers borrow from
+    /// `self`.
+    pub fn to_c(&self) -> Vec<*const c_char> {
+        let mut props = self.identity();
+
+        macro_rules! add_optional {
+            ($key:expr, $val:expr) => {
+                if let Some(ref v) = $val {
+                    props.push($key.as_ptr() as *const c_char);
+                    props.push(v.as_ptr() as *const c_char);
+                }
+            };
+        }
+
+        add_optional!(
+            rsolace_sys::SOLCLIENT_ENDPOINT_PROP_ACCESSTYPE,
+            self.access_type
+        );
+        add_optional!(
+            rsolace_sys::SOLCLIENT_ENDPOINT_PROP_PERMISSION,
+            self.permission
+        );
+        add_optional!(rsolace_sys::SOLCLIENT_ENDPOINT_PROP_QUOTA_MB, self.quota_mb);
+        add_optional!(
+            rsolace_sys::SOLCLIENT_ENDPOINT_PROP_MAXMSG_SIZE,
+            self.max_msg_size
+        );
+        add_optional!(
+            rsolace_sys::SOLCLIENT_ENDPOINT_PROP_MAXMSG_REDELIVERY,
+            self.max_msg_redelivery
+        );
+        add_optional!(
+            rsolace_sys::SOLCLIENT_ENDPOINT_PROP_RESPECTS_MSG_TTL,
+            self.respects_msg_ttl
+        );
+        add_optional!(
+            rsolace_sys::SOLCLIENT_ENDPOINT_PROP_DISCARD_BEHAVIOR,
+            self.discard_behavior
+        );
+
+        props.push(null());
+        props
+    }
+
+    /// Only the properties that identify the endpoint, as expected by
+    /// deprovision and topic subscription calls.
+    pub fn to_c_identity(&self) -> Vec<*const c_char> {
+        let mut props = self.identity();
+        props.push(null());
+        props
+    }
+
+    fn identity(&self) -> Vec<*const c_char> {
+        let mut props = Vec::with_capacity(21);
+        props.push(rsolace_sys::SOLCLIENT_ENDPOINT_PROP_ID.as_ptr() as *const c_char);
+        props.push(self.id.as_ptr() as *const c_char);
+        props.push(rsolace_sys::SOLCLIENT_ENDPOINT_PROP_NAME.as_ptr() as *const c_char);
+        props.push(self.name.as_ptr() as *const c_char);
+        props.push(rsolace_sys::SOLCLIENT_ENDPOINT_PROP_DURABLE.as_ptr() as *const c_char);
+        props.push(self.durable.as_ptr() as *const c_char);
+        props
+    }
+
+    /// - `None` → property not sent to Solace
+    /// - `Some(value)` → property sent with the value
+    pub fn access_type(mut self, access_type: Option<EndpointAccessType>) -> Self {
+        self.access_type = access_type.map(|v| v.as_prop());
+        self
+    }
+
+    /// - `None` → property not sent to Solace
+    /// - `Some(value)` → property sent with the value
+    pub fn permission(mut self, permission: Option<EndpointPermission>) -> Self {
+        self.permission = permission.map(|v| v.as_prop());
+        self
+    }
+
+    /// Spool quota in MB.
+    /// - `None` → property not sent to Solace
+    /// - `Some(value)` → property sent with the value
+    pub fn quota_mb(mut self, quota_mb: Option<u32>) -> Self {
+        self.quota_mb = quota_mb.map(|v| v.to_cstring());
+        self
+    }
+
+    /// Largest message, in bytes, the endpoint accepts.
+    /// - `None` → property not sent to Solace
+    /// - `Some(value)` → property sent with the value
+    pub fn max_msg_size(mut self, max_msg_size: Option<u32>) -> Self {
+        self.max_msg_size = max_msg_size.map(|v| v.to_cstring());
+        self
+    }
+
+    /// Deliveries after which a message is discarded, or moved to the
+    /// dead message queue when it is DMQ eligible (0 = retry forever).
+    /// - `None` → property not sent to Solace
+    /// - `Some(value)` → property sent with the value
+    pub fn max_msg_redelivery(mut self, max_msg_redelivery: Option<u8>) -> Self {
+        self.max_msg_redelivery = max_msg_redelivery.map(|v| u32::from(v).to_cstring());
+        self
+    }
+
+    /// Whether message TTLs are enforced; expired messages then go to the
+    /// dead message queue when they are DMQ eligible.
+    /// - `None` → property not sent to Solace
+    /// - `Some(value)` → property sent with the value
+    pub fn respects_msg_ttl(mut self, respects_msg_ttl: Option<bool>) -> Self {
+        self.respects_msg_ttl = respects_msg_ttl.map(|v| v.to_cstring());
+        self
+    }
+
+    /// Set both DMQ related properties from `dmq`, replacing earlier
+    /// [`EndpointProps::max_msg_redelivery`] /
+    /// [`EndpointProps::respects_msg_ttl`] values.
+    pub fn dmq(self, dmq: DmqSettings) -> Self {
+        self.max_msg_redelivery(dmq.max_msg_redelivery)
+            .respects_msg_ttl(dmq.respects_msg_ttl)
+    }
+
+    /// Whether publishers are notified (`RejectedMsgError`) when a message
+    /// is discarded because the endpoint is full or shut down.
+    /// - `None` → property not sent to Solace
+    /// - `Some(value)` → property sent with the value
+    pub fn discard_notify_sender(mut self, notify: Option<bool>) -> Self {
+        self.discard_behavior = notify.map(|notify| -> &'static [u8] {
+            if notify {
+                rsolace_sys::SOLCLIENT_ENDPOINT_PROP_DISCARD_NOTIFY_SENDER_ON
+            } else {
+                rsolace_sys::SOLCLIENT_ENDPOINT_PROP_DISCARD_NOTIFY_SENDER_OFF
+            }
+        });
+        self
+    }
+
+    pub fn name(&self) -> String {
+        self.name.to_string_lossy().into_owned()
+    }
+
+    pub fn is_queue(&self) -> bool {
+        self.id == rsolace_sys::SOLCLIENT_ENDPOINT_PROP_QUEUE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_endpoint_props_queue() {
+        let props = EndpointProps::queue("orders/in");
+        assert_eq!(props.name(), "orders/in");
+        // 3 identity pairs + null terminator
+        assert_eq!(props.to_c().len(), 7);
+        assert_eq!(props.to_c_identity().len(), 7);
+    }
+
+    #[test]
+    fn test_endpoint_props_optional() {
+        let props = EndpointProps::topic_endpoint("te")
+            .access_type(Some(EndpointAccessType::Exclusive))
+            .permission(Some(EndpointPermission::Consume))
+            .quota_mb(Some(100))
+            .max_msg_redelivery(Some(3))
+            .respects_msg_ttl(Some(true))
+            .discard_notify_sender(None);
+        let c_props = props.to_c();
+        assert_eq!(c_props.len(), 17);
+        assert!(c_props.last().unwrap().is_null());
+        let id = unsafe { CStr::from_ptr(c_props[1]) };
+        assert_eq!(
+            id.to_bytes_with_nul(),
+            rsolace_sys::SOLCLIENT_ENDPOINT_PROP_TE
+        );
+        // identity stays the same regardless of the optional properties
+        assert_eq!(props.to_c_identity().len(), 7);
+    }
+
+    #[test]
+    fn test_endpoint_props_dmq() {
+        let props = EndpointProps::queue("orders/in").dmq(DmqSettings {
+            max_msg_redelivery: Some(255),
+            respects_msg_ttl: None,
+        });
+        // 3 identity pairs + max redelivery + null terminator
+        let c_props = props.to_c();
+        assert_eq!(c_props.len(), 9);
+        let max_redelivery = unsafe { CStr::from_ptr(c_props[7]) };
+        assert_eq!(max_redelivery.to_str().unwrap(), "255");
+    }
+}
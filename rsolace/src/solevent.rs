@@ -1,6 +1,9 @@
-use super::types::SolClientSessionEvent;
+use super::types::{
+    ErrorInfo, SolClientFlowEvent, SolClientSessionEvent, SolClientSubCode, SolClientSubCodeOrRaw,
+};
+use enum_primitive::FromPrimitive;
 use snafu::prelude::{ensure, Snafu};
-use snafu::ResultExt;
+use snafu::{OptionExt, ResultExt};
 use std::ffi::CStr;
 
 #[derive(Debug, Clone)]
@@ -27,6 +30,8 @@ pub enum SolEventError {
     NullInfoPtr,
     #[snafu(display("Info UTF-8 error"))]
     InfoUtf8 { source: std::str::Utf8Error },
+    #[snafu(display("Unknown flow event {flow_event}"))]
+    UnknownFlowEvent { flow_event: i64 },
 }
 
 impl SolEvent {
@@ -80,3 +85,82 @@ impl SolEvent {
         }
     }
 }
+
+/// An event raised on a single guaranteed-messaging flow.
+#[derive(Debug, Clone)]
+pub struct SolFlowEvent {
+    pub flow_event: SolClientFlowEvent,
+    pub response_code: u32,
+    pub info: String,
+    /// Error details reported with `DownError`, `BindFailedError` and
+    /// `RejectedMsgError`; `None` for the other events.
+    pub error: Option<ErrorInfo>,
+}
+
+impl SolFlowEvent {
+    pub fn new(flow_event: SolClientFlowEvent, response_code: u32, info: &str) -> SolFlowEvent {
+        SolFlowEvent {
+            flow_event,
+            response_code,
+            info: info.to_string(),
+            error: None,
+        }
+    }
+
+    /// # Safety
+    /// `event_p` must be valid, and this must be called from the flow event
+    /// callback so the last error info belongs to this event.
+    pub unsafe fn from_ptr(
+        event_p: rsolace_sys::solClient_flow_eventCallbackInfo_pt,
+    ) -> Result<SolFlowEvent, SolEventError> {
+        ensure!(!event_p.is_null(), NullEventPtrSnafu);
+        let event = *event_p;
+
+        // Unlike session events, flow events may come without info.
+        let info = if event.info_p.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(event.info_p)
+                .to_str()
+                .context(InfoUtf8Snafu)?
+                .to_owned()
+        };
+
+        // A newer C library may raise events this enum does not know yet.
+        let code = i64::from(event.flowEvent);
+        let flow_event = SolClientFlowEvent::from_i64(code)
+            .context(UnknownFlowEventSnafu { flow_event: code })?;
+        let error = match flow_event {
+            SolClientFlowEvent::DownError
+            | SolClientFlowEvent::BindFailedError
+            | SolClientFlowEvent::RejectedMsgError => ErrorInfo::take_last(),
+            _ => None,
+        };
+
+        Ok(SolFlowEvent {
+            flow_event,
+            response_code: event.responseCode,
+            info,
+            error,
+        })
+    }
+
+    pub fn get_flow_event_string(&self) -> String {
+        unsafe {
+            let res = rsolace_sys::solClient_flow_eventToString(
+                self.flow_event as rsolace_sys::solClient_flow_event_t,
+            );
+            CStr::from_ptr(res).to_str().unwrap().to_owned()
+        }
+    }
+
+    /// Whether the broker took the flow down to start a replay on its
+    /// endpoint.
+    pub fn is_replay_started(&self) -> bool {
+        self.flow_event == SolClientFlowEvent::DownError
+            && self.error.as_ref().map(|e| &e.sub_code)
+                == Some(&SolClientSubCodeOrRaw::SubCode(
+                    SolClientSubCode::ReplayStarted,
+                ))
+    }
+}
@@ -0,0 +1,942 @@
+//! Guaranteed-messaging consumer flows.
+//!
+//! A [`SolFlow`] binds to an endpoint on the broker (currently a durable
+//! queue) and delivers the spooled messages through the same kanal
+//! sync/async receiver pattern that [`crate::solclient::SolClient`] uses
+//! for Direct messages.
+
+use super::solevent::SolFlowEvent;
+use super::solmsg::{ReplicationGroupMsgId, SolMsg, SolMsgError};
+use super::types::{ErrorInfo, SolClientReturnCode};
+use super::utils::ConvertToCString;
+use chrono::{DateTime, Utc};
+use enum_primitive::FromPrimitive;
+use snafu::prelude::{ensure, Snafu};
+use snafu::{OptionExt, ResultExt};
+use std::ffi::{c_void, CString};
+use std::os::raw::c_char;
+use std::ptr::{null, null_mut};
+use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+use std::sync::{Arc, Weak};
+
+#[cfg(feature = "tokio")]
+use kanal::AsyncReceiver;
+use kanal::{unbounded, Receiver, Sender};
+
+#[derive(Debug, Snafu, PartialEq)]
+pub enum SolFlowError {
+    #[snafu(display("SolFlow create on a session that is not connected"))]
+    SessionNotConnected,
+    #[snafu(display("SolFlow create bind to {bind_name}, code: {code:?}, Error {error:?}"))]
+    Create {
+        bind_name: String,
+        code: SolClientReturnCode,
+        error: ErrorInfo,
+    },
+    #[snafu(display("SolFlow window size {window_size} is not within 1-255"))]
+    InvalidWindowSize { window_size: u32 },
+    #[snafu(display(
+        "SolFlow {bind_name} is not bound to a durable topic endpoint of this session"
+    ))]
+    NotATopicEndpoint { bind_name: String },
+    #[snafu(display("SolFlow already closed or msg not received on a flow"))]
+    FlowClosed,
+    #[snafu(display("SolFlow ack msg {msg_id}, code: {code:?}, Error {error:?}"))]
+    Ack {
+        msg_id: u64,
+        code: SolClientReturnCode,
+        error: Option<ErrorInfo>,
+    },
+    #[snafu(display("SolFlow inside {}", source))]
+    SolMsg { source: SolMsgError },
+}
+
+/// How received guaranteed messages are acknowledged to the broker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowAckMode {
+    /// The API acknowledges each message once the receive callback returns.
+    Auto,
+    /// The application acknowledges each message with [`SolMsg::ack`] or
+    /// [`SolFlow::ack`]. Unacked messages are redelivered after a rebind.
+    Client,
+}
+
+impl FlowAckMode {
+    fn as_prop(&self) -> &'static [u8] {
+        match self {
+            FlowAckMode::Auto => rsolace_sys::SOLCLIENT_FLOW_PROP_ACKMODE_AUTO,
+            FlowAckMode::Client => rsolace_sys::SOLCLIENT_FLOW_PROP_ACKMODE_CLIENT,
+        }
+    }
+}
+
+/// Where a flow bound with [`FlowProps::replay_start`] starts replaying
+/// the broker's replay log.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayStart {
+    /// Every message in the replay log.
+    Beginning,
+    /// Messages the broker received at or after this time.
+    Date(DateTime<Utc>),
+    /// Messages the broker received after this one.
+    AfterMsg(ReplicationGroupMsgId),
+}
+
+impl ReplayStart {
+    fn to_cstring(&self) -> CString {
+        match self {
+            ReplayStart::Beginning => CString::from_vec_with_nul(
+                rsolace_sys::SOLCLIENT_FLOW_PROP_REPLAY_START_LOCATION_BEGINNING.to_vec(),
+            )
+            .unwrap(),
+            ReplayStart::Date(date) => CString::new(format!("DATE:{}", date.timestamp())).unwrap(),
+            ReplayStart::AfterMsg(rgmid) => CString::new(format!("RGMID:{}", rgmid)).unwrap(),
+        }
+    }
+}
+
+/// Sent on [`SolFlow::get_replay_receiver`] when the broker starts a
+/// replay on the flow's endpoint. The broker unbinds the flow when the
+/// replay starts; bind a new one to receive the replayed messages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayStarted {
+    pub info: String,
+}
+
+/// Properties used to bind a [`SolFlow`] to an endpoint.
+///
+/// ```ignore
+/// let props = FlowProps::default()
+///     .queue("orders/in")
+///     .window_size(64);
+/// let flow = client.create_flow(props)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct FlowProps {
+    bind_entity_id: &'static [u8],
+    bind_name: CString,
+    bind_entity_durable: CString,
+    bind_blocking: CString,
+    bind_timeout_ms: CString,
+    window_size: u32,
+    window_size_c: CString,
+    start_state: CString,
+    ack_mode: FlowAckMode,
+    topic: Option<CString>,
+    max_bind_tries: Option<CString>,
+    max_unacked_messages: Option<CString>,
+    selector: Option<CString>,
+    replay_start: Option<CString>,
+    active_flow_ind: Option<CString>,
+    browser: bool,
+}
+
+impl FlowProps {
+    /// Convert flow properties to a C-compatible, null-terminated property
+    /// array. The returned pointers borrow from `self`.
+    pub fn to_c(&self) -> Vec<*const c_char> {
+        let mut props = Vec::with_capacity(24);
+
+        macro_rules! add_prop {
+            ($key:expr, $val:expr) => {
+                props.push($key.as_ptr() as *const c_char);
+                props.push($val.as_ptr() as *const c_char);
+            };
+        }
+
+        macro_rules! add_optional {
+            ($key:expr, $val:expr) => {
+                if let Some(ref v) = $val {
+                    props.push($key.as_ptr() as *const c_char);
+                    props.push(v.as_ptr() as *const c_char);
+                }
+            };
+        }
+
+        add_prop!(
+            rsolace_sys::SOLCLIENT_FLOW_PROP_BIND_ENTITY_ID,
+            self.bind_entity_id
+        );
+        add_prop!(rsolace_sys::SOLCLIENT_FLOW_PROP_BIND_NAME, self.bind_name);
+        add_prop!(
+            rsolace_sys::SOLCLIENT_FLOW_PROP_BIND_ENTITY_DURABLE,
+            self.bind_entity_durable
+        );
+        add_prop!(
+            rsolace_sys::SOLCLIENT_FLOW_PROP_BIND_BLOCKING,
+            self.bind_blocking
+        );
+        add_prop!(
+            rsolace_sys::SOLCLIENT_FLOW_PROP_BIND_TIMEOUT_MS,
+            self.bind_timeout_ms
+        );
+        add_prop!(
+            rsolace_sys::SOLCLIENT_FLOW_PROP_WINDOWSIZE,
+            self.window_size_c
+        );
+        add_prop!(
+            rsolace_sys::SOLCLIENT_FLOW_PROP_START_STATE,
+            self.start_state
+        );
+        add_prop!(
+            rsolace_sys::SOLCLIENT_FLOW_PROP_ACKMODE,
+            self.ack_mode.as_prop()
+        );
+        add_optional!(rsolace_sys::SOLCLIENT_FLOW_PROP_TOPIC, self.topic);
+        add_optional!(
+            rsolace_sys::SOLCLIENT_FLOW_PROP_MAX_BIND_TRIES,
+            self.max_bind_tries
+        );
+        add_optional!(
+            rsolace_sys::SOLCLIENT_FLOW_PROP_MAX_UNACKED_MESSAGES,
+            self.max_unacked_messages
+        );
+        add_optional!(rsolace_sys::SOLCLIENT_FLOW_PROP_SELECTOR, self.selector);
+        add_optional!(
+            rsolace_sys::SOLCLIENT_FLOW_PROP_REPLAY_START_LOCATION,
+            self.replay_start
+        );
+        add_optional!(
+            rsolace_sys::SOLCLIENT_FLOW_PROP_ACTIVE_FLOW_IND,
+            self.active_flow_ind
+        );
+        if self.browser {
+            add_prop!(
+                rsolace_sys::SOLCLIENT_FLOW_PROP_BROWSER,
+                rsolace_sys::SOLCLIENT_PROP_ENABLE_VAL
+            );
+        }
+
+        props.push(null());
+        props
+    }
+
+    /// Bind to the named durable queue.
+    pub fn queue(mut self, name: &str) -> Self {
+        self.bind_entity_id = rsolace_sys::SOLCLIENT_FLOW_PROP_BIND_ENTITY_QUEUE;
+        self.bind_name = name.to_cstring();
+        self.bind_entity_durable = true.to_cstring();
+        self.topic = None;
+        self
+    }
+
+    /// Bind to a temporary queue named by
+    /// `solClient_session_createTemporaryQueueName`. The broker creates the
+    /// queue on bind and removes it when the flow is unbound.
+    pub fn temporary_queue(mut self, name: &str) -> Self {
+        self.bind_entity_id = rsolace_sys::SOLCLIENT_FLOW_PROP_BIND_ENTITY_QUEUE;
+        self.bind_name = name.to_cstring();
+        self.bind_entity_durable = false.to_cstring();
+        self.topic = None;
+        self
+    }
+
+    /// Bind to the named durable topic endpoint, attracting messages
+    /// published to `topic`.
+    ///
+    /// Binding with a different topic than the endpoint's current one
+    /// changes its subscription; the broker then deletes the messages
+    /// already spooled for the old topic. Move a bound consumer to a new
+    /// topic with [`crate::solclient::SolClient::rebind_topic_endpoint`].
+    pub fn topic_endpoint(mut self, name: &str, topic: &str) -> Self {
+        self.bind_entity_id = rsolace_sys::SOLCLIENT_FLOW_PROP_BIND_ENTITY_TE;
+        self.bind_name = name.to_cstring();
+        self.bind_entity_durable = true.to_cstring();
+        self.topic = Some(topic.to_cstring());
+        self
+    }
+
+    /// Bind to a temporary topic endpoint created by the broker for this
+    /// flow and removed when the flow is unbound.
+    pub fn temporary_topic_endpoint(mut self, topic: &str) -> Self {
+        self.bind_entity_id = rsolace_sys::SOLCLIENT_FLOW_PROP_BIND_ENTITY_SUB;
+        self.bind_name = "".to_cstring();
+        self.bind_entity_durable = false.to_cstring();
+        self.topic = Some(topic.to_cstring());
+        self
+    }
+
+    pub fn bind_blocking(mut self, blocking: bool) -> Self {
+        self.bind_blocking = blocking.to_cstring();
+        self
+    }
+
+    pub fn bind_timeout_ms(mut self, timeout: u32) -> Self {
+        self.bind_timeout_ms = timeout.to_cstring();
+        self
+    }
+
+    /// Number of messages the broker may send before the flow
+    /// acknowledges them (1-255). Creating a flow with a size outside that
+    /// range fails with [`SolFlowError::InvalidWindowSize`].
+    pub fn window_size(mut self, window_size: u32) -> Self {
+        self.window_size = window_size;
+        self.window_size_c = window_size.to_cstring();
+        self
+    }
+
+    /// Whether the flow starts delivering immediately after the bind.
+    /// When `false`, call [`SolFlow::start`] once the receivers are ready.
+    pub fn start_state(mut self, started: bool) -> Self {
+        self.start_state = started.to_cstring();
+        self
+    }
+
+    /// Defaults to [`FlowAckMode::Auto`].
+    pub fn ack_mode(mut self, ack_mode: FlowAckMode) -> Self {
+        self.ack_mode = ack_mode;
+        self
+    }
+
+    /// - `None` → property not sent to Solace
+    /// - `Some(value)` → property sent with the value
+    pub fn max_bind_tries(mut self, tries: Option<u32>) -> Self {
+        self.max_bind_tries = tries.map(|v| v.to_cstring());
+        self
+    }
+
+    /// - `None` → property not sent to Solace (`-1`, unlimited)
+    /// - `Some(value)` → property sent with the value
+    pub fn max_unacked_messages(mut self, max: Option<i32>) -> Self {
+        self.max_unacked_messages = max.map(|v| v.to_cstring());
+        self
+    }
+
+    /// SQL-92 selector on the messages' user properties; the broker only
+    /// delivers the matching ones. Validate or test it locally with
+    /// [`crate::solselector::Selector`].
+    /// - `None` → property not sent to Solace
+    /// - `Some(value)` → property sent with the value
+    pub fn selector(mut self, selector: Option<&str>) -> Self {
+        self.selector = selector.map(|v| v.to_cstring());
+        self
+    }
+
+    /// Replay messages from the broker's replay log before the live ones.
+    /// The bind fails if the start location is no longer in the log.
+    /// - `None` → property not sent to Solace
+    /// - `Some(value)` → property sent with the value
+    pub fn replay_start(mut self, start: Option<ReplayStart>) -> Self {
+        self.replay_start = start.map(|v| v.to_cstring());
+        self
+    }
+
+    /// Raise `Active` / `Inactive` flow events when this flow becomes, or
+    /// stops being, the one receiving messages from an exclusive queue.
+    /// - `None` → property not sent to Solace
+    /// - `Some(value)` → property sent with the value
+    pub fn active_flow_ind(mut self, active_flow_ind: Option<bool>) -> Self {
+        self.active_flow_ind = active_flow_ind.map(|v| v.to_cstring());
+        self
+    }
+
+    /// Browse the bound queue instead of consuming from it: messages stay
+    /// spooled after delivery, and acknowledging one deletes it from the
+    /// queue. Only valid for queues. See [`crate::solbrowser::QueueBrowser`].
+    pub fn browser(mut self, browser: bool) -> Self {
+        self.browser = browser;
+        self
+    }
+
+    pub(crate) fn bind_name(&self) -> String {
+        self.bind_name.to_string_lossy().into_owned()
+    }
+
+    fn is_durable_topic_endpoint(&self) -> bool {
+        self.bind_entity_id == rsolace_sys::SOLCLIENT_FLOW_PROP_BIND_ENTITY_TE
+    }
+}
+
+impl Default for FlowProps {
+    fn default() -> Self {
+        Self {
+            bind_entity_id: rsolace_sys::SOLCLIENT_FLOW_PROP_BIND_ENTITY_QUEUE,
+            bind_name: "".to_cstring(),
+            bind_entity_durable: true.to_cstring(),
+            bind_blocking: true.to_cstring(),
+            bind_timeout_ms: 10000.to_cstring(),
+            window_size: 255,
+            window_size_c: 255.to_cstring(),
+            start_state: true.to_cstring(),
+            ack_mode: FlowAckMode::Auto,
+            topic: None,
+            max_bind_tries: None,
+            max_unacked_messages: None,
+            selector: None,
+            replay_start: None,
+            active_flow_ind: None,
+            browser: false,
+        }
+    }
+}
+
+pub(crate) struct SolFlowInner {
+    /// Handed to every received `SolMsg` so `SolMsg::ack` can reach the
+    /// flow without keeping it alive.
+    self_weak: Weak<SolFlowInner>,
+    /// Set once `solClient_session_createFlow` returns. Kept atomic because
+    /// the C context thread may already deliver messages to this struct
+    /// (through `user_p`) before the create call hands the pointer back.
+    flow_p: AtomicPtr<c_void>,
+    /// Liveness of the session (and transacted session, if any) that owns
+    /// the flow, cleared right before the owner is destroyed. Destroying
+    /// the owner destroys its flows, so a flow dropped afterwards must not
+    /// call `solClient_flow_destroy` on the dangling pointer.
+    owners_alive: Vec<Arc<AtomicBool>>,
+    msg_sender: Sender<SolMsg>,
+    msg_receiver: Receiver<SolMsg>,
+    event_sender: Sender<SolFlowEvent>,
+    event_receiver: Receiver<SolFlowEvent>,
+    replay_sender: Sender<ReplayStarted>,
+    replay_receiver: Receiver<ReplayStarted>,
+}
+
+/// A consumer flow bound to a guaranteed-messaging endpoint.
+///
+/// Created with [`crate::solclient::SolClient::create_flow`]. Dropping the
+/// `SolFlow` unbinds and destroys the flow; it must be dropped before the
+/// `SolClient` reconnects or is dropped for the unbind to reach the broker.
+pub struct SolFlow {
+    // Arc gives the C callbacks a stable `user_p` for the life of the flow.
+    inner: Arc<SolFlowInner>,
+    // Kept to bind the endpoint again on a topic change.
+    props: FlowProps,
+}
+
+impl SolFlow {
+    /// Create a flow on `session_p`.
+    pub(crate) fn new(
+        session_p: rsolace_sys::solClient_opaqueSession_pt,
+        session_alive: Arc<AtomicBool>,
+        props: &FlowProps,
+    ) -> Result<SolFlow, SolFlowError> {
+        ensure!(!session_p.is_null(), SessionNotConnectedSnafu);
+        Self::create(
+            vec![session_alive],
+            props,
+            |props_p, flow_p, func_info_p| unsafe {
+                rsolace_sys::solClient_session_createFlow(
+                    props_p,
+                    session_p,
+                    flow_p,
+                    func_info_p,
+                    std::mem::size_of::<rsolace_sys::solClient_flow_createFuncInfo_t>(),
+                )
+            },
+        )
+    }
+
+    /// Create a flow inside the transacted session `transacted_session_p`.
+    /// Messages received on it are settled by the transaction's commit or
+    /// rollback.
+    pub(crate) fn new_transacted(
+        transacted_session_p: rsolace_sys::solClient_opaqueTransactedSession_pt,
+        owners_alive: Vec<Arc<AtomicBool>>,
+        props: &FlowProps,
+    ) -> Result<SolFlow, SolFlowError> {
+        ensure!(!transacted_session_p.is_null(), SessionNotConnectedSnafu);
+        Self::create(owners_alive, props, |props_p, flow_p, func_info_p| unsafe {
+            rsolace_sys::solClient_transactedSession_createFlow(
+                props_p,
+                transacted_session_p,
+                flow_p,
+                func_info_p,
+                std::mem::size_of::<rsolace_sys::solClient_flow_createFuncInfo_t>(),
+            )
+        })
+    }
+
+    fn create(
+        owners_alive: Vec<Arc<AtomicBool>>,
+        props: &FlowProps,
+        create_fn: impl FnOnce(
+            rsolace_sys::solClient_propertyArray_pt,
+            *mut rsolace_sys::solClient_opaqueFlow_pt,
+            *mut rsolace_sys::solClient_flow_createFuncInfo_t,
+        ) -> rsolace_sys::solClient_returnCode_t,
+    ) -> Result<SolFlow, SolFlowError> {
+        ensure!(
+            (1..=255).contains(&props.window_size),
+            InvalidWindowSizeSnafu {
+                window_size: props.window_size
+            }
+        );
+        let (msg_sender, msg_receiver) = unbounded();
+        let (event_sender, event_receiver) = unbounded();
+        let (replay_sender, replay_receiver) = unbounded();
+        let inner = Arc::new_cyclic(|self_weak| SolFlowInner {
+            self_weak: self_weak.clone(),
+            flow_p: AtomicPtr::new(null_mut()),
+            owners_alive,
+            msg_sender,
+            msg_receiver,
+            event_sender,
+            event_receiver,
+            replay_sender,
+            replay_receiver,
+        });
+        ensure!(inner.owners_alive(), SessionNotConnectedSnafu);
+
+        let mut flow_props = props.to_c();
+        let user_p = Arc::as_ptr(&inner) as *mut c_void;
+        let mut func_info = rsolace_sys::solClient_flow_createFuncInfo_t {
+            rxInfo: rsolace_sys::solClient_flow_createRxCallbackFuncInfo_t {
+                callback_p: null_mut(),
+                user_p: null_mut(),
+            },
+            eventInfo: rsolace_sys::solClient_flow_createEventCallbackFuncInfo_t {
+                callback_p: Some(flow_event_callback),
+                user_p,
+            },
+            rxMsgInfo: rsolace_sys::solClient_flow_createRxMsgCallbackFuncInfo_t {
+                callback_p: Some(flow_message_receive_callback),
+                user_p,
+            },
+        };
+        let mut flow_p: rsolace_sys::solClient_opaqueFlow_pt = null_mut();
+        let rt_code = create_fn(flow_props.as_mut_ptr(), &mut flow_p, &mut func_info);
+        let rt_code = SolClientReturnCode::from_i32(rt_code).unwrap();
+        // Non-blocking binds report IN_PROGRESS and confirm with a flow
+        // UP_NOTICE event later on.
+        ensure!(
+            rt_code == SolClientReturnCode::Ok || rt_code == SolClientReturnCode::InProgress,
+            CreateSnafu {
+                bind_name: props.bind_name(),
+                code: rt_code,
+                error: ErrorInfo::take_last().unwrap(),
+            }
+        );
+        inner.flow_p.store(flow_p, Ordering::Release);
+        Ok(SolFlow {
+            inner,
+            props: props.clone(),
+        })
+    }
+
+    /// Unbind this flow from its durable topic endpoint and bind the
+    /// endpoint again on `session_p`, attracting `topic` instead. The
+    /// flow must have been created on that same session.
+    pub(crate) fn rebind_topic(
+        self,
+        session_p: rsolace_sys::solClient_opaqueSession_pt,
+        session_alive: Arc<AtomicBool>,
+        topic: &str,
+    ) -> Result<SolFlow, SolFlowError> {
+        let owned_by_session = matches!(
+            self.inner.owners_alive.as_slice(),
+            [owner] if Arc::ptr_eq(owner, &session_alive)
+        );
+        ensure!(
+            self.props.is_durable_topic_endpoint() && owned_by_session,
+            NotATopicEndpointSnafu {
+                bind_name: self.props.bind_name()
+            }
+        );
+        let props = self.props.clone();
+        let name = props.bind_name();
+        // Unbind before the new bind: an exclusive endpoint takes one flow.
+        drop(self);
+        SolFlow::new(
+            session_p,
+            session_alive,
+            &props.topic_endpoint(&name, topic),
+        )
+    }
+
+    /// Resume message delivery on a flow created with
+    /// `start_state(false)` or previously stopped.
+    /// `Fail` once the owning session was destroyed.
+    pub fn start(&self) -> SolClientReturnCode {
+        let Some(flow_p) = self.inner.live_flow_p() else {
+            return SolClientReturnCode::Fail;
+        };
+        let rt_code = unsafe { rsolace_sys::solClient_flow_start(flow_p) };
+        SolClientReturnCode::from_i32(rt_code).unwrap()
+    }
+
+    /// Stop message delivery. Messages already in flight may still arrive.
+    /// `Fail` once the owning session was destroyed.
+    pub fn stop(&self) -> SolClientReturnCode {
+        let Some(flow_p) = self.inner.live_flow_p() else {
+            return SolClientReturnCode::Fail;
+        };
+        let rt_code = unsafe { rsolace_sys::solClient_flow_stop(flow_p) };
+        SolClientReturnCode::from_i32(rt_code).unwrap()
+    }
+
+    /// Acknowledge `msg`, which must have been received on this flow.
+    /// Equivalent to [`SolMsg::ack`].
+    pub fn ack(&self, msg: &SolMsg) -> Result<(), SolFlowError> {
+        self.inner.ack(msg)
+    }
+
+    pub fn get_msg_receiver(&self) -> Receiver<SolMsg> {
+        self.inner.msg_receiver.clone()
+    }
+
+    #[cfg(feature = "tokio")]
+    pub fn get_async_msg_receiver(&self) -> AsyncReceiver<SolMsg> {
+        self.inner.msg_receiver.as_async().clone()
+    }
+
+    /// Events raised on this flow: up/down, bind failures, active/inactive
+    /// changes (with [`FlowProps::active_flow_ind`]) and reconnects.
+    pub fn get_event_receiver(&self) -> Receiver<SolFlowEvent> {
+        self.inner.event_receiver.clone()
+    }
+
+    #[cfg(feature = "tokio")]
+    pub fn get_async_event_receiver(&self) -> AsyncReceiver<SolFlowEvent> {
+        self.inner.event_receiver.as_async().clone()
+    }
+
+    /// Notified when the broker starts a replay on this flow's endpoint.
+    pub fn get_replay_receiver(&self) -> Receiver<ReplayStarted> {
+        self.inner.replay_receiver.clone()
+    }
+
+    #[cfg(feature = "tokio")]
+    pub fn get_async_replay_receiver(&self) -> AsyncReceiver<ReplayStarted> {
+        self.inner.replay_receiver.as_async().clone()
+    }
+
+    /// Null once the owning session was destroyed.
+    pub fn get_ptr(&self) -> rsolace_sys::solClient_opaqueFlow_pt {
+        self.inner.live_flow_p().unwrap_or(null_mut())
+    }
+}
+
+impl SolFlowInner {
+    fn flow_p(&self) -> rsolace_sys::solClient_opaqueFlow_pt {
+        self.flow_p.load(Ordering::Acquire)
+    }
+
+    fn owners_alive(&self) -> bool {
+        self.owners_alive
+            .iter()
+            .all(|alive| alive.load(Ordering::Acquire))
+    }
+
+    /// The flow pointer, unless the flow or one of its owners is gone and
+    /// the pointer dangles.
+    fn live_flow_p(&self) -> Option<rsolace_sys::solClient_opaqueFlow_pt> {
+        let flow_p = self.flow_p();
+        (!flow_p.is_null() && self.owners_alive()).then_some(flow_p)
+    }
+
+    pub(crate) fn ack(&self, msg: &SolMsg) -> Result<(), SolFlowError> {
+        let flow_p = self.live_flow_p().context(FlowClosedSnafu)?;
+        let msg_id = msg.get_msg_id().context(SolMsgSnafu)?;
+        let rt_code = unsafe { rsolace_sys::solClient_flow_sendAck(flow_p, msg_id) };
+        let rt_code = SolClientReturnCode::from_i32(rt_code).unwrap();
+        ensure!(
+            rt_code == SolClientReturnCode::Ok,
+            AckSnafu {
+                msg_id,
+                code: rt_code,
+                error: ErrorInfo::take_last(),
+            }
+        );
+        Ok(())
+    }
+}
+
+impl Drop for SolFlowInner {
+    fn drop(&mut self) {
+        let mut flow_p = self.flow_p();
+        if !flow_p.is_null() && self.owners_alive() {
+            unsafe {
+                rsolace_sys::solClient_flow_destroy(&mut flow_p);
+            }
+            self.flow_p.store(null_mut(), Ordering::Release);
+        }
+        tracing::debug!("solace flow dropped");
+    }
+}
+
+unsafe extern "C" fn flow_message_receive_callback(
+    _opaque_flow_p: rsolace_sys::solClient_opaqueFlow_pt,
+    msg_p: rsolace_sys::solClient_opaqueMsg_pt,
+    user_p: *mut c_void,
+) -> rsolace_sys::solClient_rxMsgCallback_returnCode_t {
+    match SolMsg::from_ptr(msg_p) {
+        Ok(mut msg) => {
+            let self_ref: &SolFlowInner = &*(user_p as *const SolFlowInner);
+            msg.set_flow(self_ref.self_weak.clone());
+            if let Err(e) = self_ref.msg_sender.send(msg) {
+                tracing::error!("send flow msg to channel error: {}", e);
+            }
+        }
+        Err(e) => {
+            tracing::error!("flow msg from ptr error: {}", e);
+        }
+    }
+    rsolace_sys::solClient_rxMsgCallback_returnCode_SOLCLIENT_CALLBACK_TAKE_MSG
+}
+
+unsafe extern "C" fn flow_event_callback(
+    _opaque_flow_p: rsolace_sys::solClient_opaqueFlow_pt,
+    event_info_p: rsolace_sys::solClient_flow_eventCallbackInfo_pt,
+    user_p: *mut c_void,
+) {
+    match SolFlowEvent::from_ptr(event_info_p) {
+        Ok(event) => {
+            tracing::info!(
+                "flow event: {}, response code: {}, info: {}",
+                event.get_flow_event_string(),
+                event.response_code,
+                event.info
+            );
+            let self_ref: &SolFlowInner = &*(user_p as *const SolFlowInner);
+            if event.is_replay_started() {
+                let replay = ReplayStarted {
+                    info: event.info.clone(),
+                };
+                if let Err(e) = self_ref.replay_sender.send(replay) {
+                    tracing::error!("send flow replay event to channel error: {}", e);
+                }
+            }
+            if let Err(e) = self_ref.event_sender.send(event) {
+                tracing::error!("send flow event to channel error: {}", e);
+            }
+        }
+        Err(e) => {
+            tracing::error!("flow event from ptr error: {}", e);
+        }
+    }
+}
+
+// SAFETY: the flow pointer is only handed to thread-safe C API calls
+// (start/stop/sendAck/destroy) and all other state is made of thread-safe types.
+unsafe impl Send for SolFlowInner {}
+unsafe impl Sync for SolFlowInner {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solevent::SolEventError;
+    use crate::types::SolClientFlowEvent;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_default_flow_props() {
+        let c_props = FlowProps::default().to_c();
+        // 8 required pairs + null terminator
+        assert_eq!(c_props.len(), 17);
+        assert!(c_props.last().unwrap().is_null());
+    }
+
+    #[test]
+    fn test_flow_props_queue() {
+        let props = FlowProps::default()
+            .queue("orders/in")
+            .max_bind_tries(Some(5));
+        assert_eq!(props.bind_name(), "orders/in");
+        assert_eq!(
+            props.bind_entity_id,
+            rsolace_sys::SOLCLIENT_FLOW_PROP_BIND_ENTITY_QUEUE
+        );
+        // one optional pair on top of the defaults
+        assert_eq!(props.to_c().len(), 19);
+    }
+
+    #[test]
+    fn test_invalid_window_size_fails_flow_creation() {
+        for window_size in [0, 256] {
+            let props = FlowProps::default()
+                .queue("orders/in")
+                .window_size(window_size);
+            let res = SolFlow::create(vec![], &props, |_, _, _| unreachable!());
+            assert_eq!(
+                res.err(),
+                Some(SolFlowError::InvalidWindowSize { window_size })
+            );
+        }
+    }
+
+    #[test]
+    fn test_flow_props_topic_endpoint() {
+        let props = FlowProps::default().topic_endpoint("legacy-te", "orders/>");
+        assert_eq!(props.bind_name(), "legacy-te");
+        assert_eq!(
+            props.bind_entity_id,
+            rsolace_sys::SOLCLIENT_FLOW_PROP_BIND_ENTITY_TE
+        );
+        // topic pair on top of the defaults
+        assert_eq!(props.to_c().len(), 19);
+
+        let props = props.temporary_topic_endpoint("orders/>");
+        assert_eq!(props.bind_name(), "");
+        assert_eq!(
+            props.bind_entity_id,
+            rsolace_sys::SOLCLIENT_FLOW_PROP_BIND_ENTITY_SUB
+        );
+        assert_eq!(props.to_c().len(), 19);
+
+        // binding a queue again drops the topic
+        assert_eq!(props.queue("q").to_c().len(), 17);
+    }
+
+    /// A flow owned by `owner` that never reached the C API.
+    fn unbound_flow(owner: &Arc<AtomicBool>, props: &FlowProps) -> SolFlow {
+        SolFlow::create(vec![owner.clone()], props, |_, _, _| {
+            rsolace_sys::solClient_returnCode_SOLCLIENT_OK
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rebind_topic_only_for_own_topic_endpoint_flows() {
+        let session_alive = Arc::new(AtomicBool::new(true));
+        let not_a_te = SolFlowError::NotATopicEndpoint {
+            bind_name: "orders/in".to_string(),
+        };
+
+        let queue_flow = unbound_flow(&session_alive, &FlowProps::default().queue("orders/in"));
+        let res = queue_flow.rebind_topic(null_mut(), session_alive.clone(), "orders/eu/>");
+        assert_eq!(res.err(), Some(not_a_te));
+
+        let te_props = FlowProps::default().topic_endpoint("legacy-te", "orders/>");
+        let other_session = Arc::new(AtomicBool::new(true));
+        let foreign_flow = unbound_flow(&other_session, &te_props);
+        let res = foreign_flow.rebind_topic(null_mut(), session_alive.clone(), "orders/eu/>");
+        assert_eq!(
+            res.err(),
+            Some(SolFlowError::NotATopicEndpoint {
+                bind_name: "legacy-te".to_string(),
+            })
+        );
+
+        // An own topic endpoint flow gets unbound and bound again, which
+        // fails here for lack of a session.
+        let te_flow = unbound_flow(&session_alive, &te_props);
+        let res = te_flow.rebind_topic(null_mut(), session_alive, "orders/eu/>");
+        assert_eq!(res.err(), Some(SolFlowError::SessionNotConnected));
+    }
+
+    #[test]
+    fn test_flow_props_temporary_queue() {
+        let props = FlowProps::default()
+            .topic_endpoint("legacy-te", "orders/>")
+            .temporary_queue("#P2P/QTMP/v:host/inbox");
+        assert_eq!(props.bind_name(), "#P2P/QTMP/v:host/inbox");
+        assert_eq!(props.bind_entity_durable, false.to_cstring());
+        assert_eq!(props.to_c().len(), 17);
+    }
+
+    #[test]
+    fn test_flow_props_selector() {
+        let props = FlowProps::default()
+            .queue("orders/in")
+            .selector(Some("region = 'eu'"));
+        let c_props = props.to_c();
+        assert_eq!(c_props.len(), 19);
+        let selector_idx = c_props
+            .iter()
+            .position(|p| *p == rsolace_sys::SOLCLIENT_FLOW_PROP_SELECTOR.as_ptr() as *const c_char)
+            .unwrap();
+        let selector = unsafe { CStr::from_ptr(c_props[selector_idx + 1]) };
+        assert_eq!(selector.to_str().unwrap(), "region = 'eu'");
+    }
+
+    #[test]
+    fn test_flow_props_replay_start() {
+        let props = FlowProps::default()
+            .queue("risk/in")
+            .replay_start(Some(ReplayStart::Beginning));
+        assert_eq!(props.to_c().len(), 19);
+        assert_eq!(
+            props.replay_start.as_ref().unwrap().as_bytes_with_nul(),
+            rsolace_sys::SOLCLIENT_FLOW_PROP_REPLAY_START_LOCATION_BEGINNING
+        );
+
+        let date = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let props = props.replay_start(Some(ReplayStart::Date(date)));
+        assert_eq!(
+            props.replay_start.as_ref().unwrap().to_str().unwrap(),
+            "DATE:1700000000"
+        );
+        assert_eq!(props.replay_start(None).to_c().len(), 17);
+    }
+
+    #[test]
+    fn test_flow_props_active_flow_ind() {
+        let props = FlowProps::default()
+            .queue("orders/in")
+            .active_flow_ind(Some(true));
+        assert_eq!(props.to_c().len(), 19);
+        assert_eq!(props.active_flow_ind(None).to_c().len(), 17);
+    }
+
+    #[test]
+    fn test_flow_event_from_ptr() {
+        let info = CString::new("flow active").unwrap();
+        let mut event_info = rsolace_sys::solClient_flow_eventCallbackInfo {
+            flowEvent: rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_ACTIVE,
+            responseCode: 0,
+            info_p: info.as_ptr(),
+        };
+        let event = unsafe { SolFlowEvent::from_ptr(&mut event_info) }.unwrap();
+        assert_eq!(event.flow_event, SolClientFlowEvent::Active);
+        assert_eq!(event.info, "flow active");
+        assert!(event.error.is_none());
+        assert!(!event.is_replay_started());
+
+        event_info.flowEvent = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_RECONNECTING;
+        event_info.info_p = null();
+        let event = unsafe { SolFlowEvent::from_ptr(&mut event_info) }.unwrap();
+        assert_eq!(event.flow_event, SolClientFlowEvent::Reconnecting);
+        assert_eq!(event.info, "");
+
+        event_info.flowEvent = 9999;
+        assert!(matches!(
+            unsafe { SolFlowEvent::from_ptr(&mut event_info) },
+            Err(SolEventError::UnknownFlowEvent { flow_event: 9999 })
+        ));
+    }
+
+    #[test]
+    fn test_flow_props_browser() {
+        let props = FlowProps::default().queue("orders/in").browser(true);
+        let c_props = props.to_c();
+        assert_eq!(c_props.len(), 19);
+        let browser_idx = c_props
+            .iter()
+            .position(|p| *p == rsolace_sys::SOLCLIENT_FLOW_PROP_BROWSER.as_ptr() as *const c_char)
+            .unwrap();
+        let browser = unsafe { CStr::from_ptr(c_props[browser_idx + 1]) };
+        assert_eq!(browser.to_str().unwrap(), "1");
+        assert_eq!(props.browser(false).to_c().len(), 17);
+    }
+
+    #[test]
+    fn test_flow_props_client_ack() {
+        let props = FlowProps::default().ack_mode(FlowAckMode::Client);
+        let c_props = props.to_c();
+        let ack_mode_idx = c_props
+            .iter()
+            .position(|p| *p == rsolace_sys::SOLCLIENT_FLOW_PROP_ACKMODE.as_ptr() as *const c_char)
+            .unwrap();
+        let ack_mode = unsafe { CStr::from_ptr(c_props[ack_mode_idx + 1]) };
+        assert_eq!(
+            ack_mode.to_bytes_with_nul(),
+            rsolace_sys::SOLCLIENT_FLOW_PROP_ACKMODE_CLIENT
+        );
+    }
+
+    #[test]
+    fn test_ack_msg_not_from_flow() {
+        let msg = SolMsg::new().unwrap();
+        assert_eq!(msg.ack(), Err(SolFlowError::FlowClosed));
+    }
+
+    #[test]
+    fn test_create_flow_without_session() {
+        let res = SolFlow::new(
+            null_mut(),
+            Arc::new(AtomicBool::new(false)),
+            &FlowProps::default().queue("q"),
+        );
+        assert_eq!(res.err(), Some(SolFlowError::SessionNotConnected));
+    }
+}
@@ -1,4 +1,6 @@
 use super::solcontainer::SolContainer;
+#[cfg(feature = "channel")]
+use super::solflow::{SolFlowError, SolFlowInner};
 use super::types::{
     SolClientCacheStatus, SolClientDeliveryMode, SolClientDestType, SolClientReturnCode,
 };
@@ -12,10 +14,16 @@ use chrono::DateTime;
 use snafu::prelude::{ensure, Snafu};
 use snafu::{OptionExt, ResultExt};
 use std::ptr::null_mut;
+#[cfg(feature = "channel")]
+use std::sync::Weak;
 
 pub struct SolMsg {
     msg_p: rsolace_sys::solClient_opaqueMsg_pt,
     user_prop_p: Option<rsolace_sys::solClient_opaqueContainer_pt>,
+    /// The flow this message was received on, so it can be acknowledged
+    /// from whichever thread ends up owning it.
+    #[cfg(feature = "channel")]
+    flow: Option<Weak<SolFlowInner>>,
     // _ph: PhantomData<&'a ()>,
     // container_p: Option<rsolace_sys::solClient_opaqueContainer_pt>,
 }
@@ -47,6 +55,8 @@ pub enum SolMsgError {
     },
     #[snafu(display("SolMsg without user prop"))]
     UserPropNotExist,
+    #[snafu(display("SolMsg invalid replication group message id {id}"))]
+    InvalidReplicationGroupMsgId { id: String },
 }
 
 // pub trait FromCptr {
@@ -68,6 +78,89 @@ impl Destination {
     }
 }
 
+/// Identifies a guaranteed message across the brokers of a replication
+/// group. Round-trips through its string form (`rmid1:...`) and can be
+/// used as a replay start location.
+///
+/// Ids from different replication groups are not comparable, so ordering
+/// is partial.
+#[derive(Clone, Copy)]
+pub struct ReplicationGroupMsgId(rsolace_sys::solClient_replicationGroupMessageId);
+
+impl ReplicationGroupMsgId {
+    const SIZE: usize = std::mem::size_of::<rsolace_sys::solClient_replicationGroupMessageId>();
+}
+
+impl PartialEq for ReplicationGroupMsgId {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.replicationGroupMessageId == other.0.replicationGroupMessageId
+    }
+}
+
+impl Eq for ReplicationGroupMsgId {}
+
+impl PartialOrd for ReplicationGroupMsgId {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        let (mut lhs, mut rhs) = (self.0, other.0);
+        let mut result: std::os::raw::c_int = 0;
+        let rt_code = unsafe {
+            rsolace_sys::solClient_replicationGroupMessageId_compare(&mut lhs, &mut rhs, &mut result)
+        };
+        if rt_code != SolClientReturnCode::Ok as i32 {
+            return None;
+        }
+        Some(result.cmp(&0))
+    }
+}
+
+impl std::fmt::Display for ReplicationGroupMsgId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut rgmid = self.0;
+        let mut buf: [c_char; 64] = [0; 64];
+        let rt_code = unsafe {
+            rsolace_sys::solClient_replicationGroupMessageId_toString(
+                &mut rgmid,
+                Self::SIZE,
+                buf.as_mut_ptr(),
+                buf.len(),
+            )
+        };
+        if rt_code != SolClientReturnCode::Ok as i32 {
+            return Err(std::fmt::Error);
+        }
+        f.write_str(&unsafe { CStr::from_ptr(buf.as_ptr()) }.to_string_lossy())
+    }
+}
+
+impl std::fmt::Debug for ReplicationGroupMsgId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ReplicationGroupMsgId({})", self)
+    }
+}
+
+impl std::str::FromStr for ReplicationGroupMsgId {
+    type Err = SolMsgError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || SolMsgError::InvalidReplicationGroupMsgId { id: s.to_string() };
+        let id = CString::new(s).map_err(|_| invalid())?;
+        let mut rgmid = rsolace_sys::solClient_replicationGroupMessageId {
+            replicationGroupMessageId: [0; 16],
+        };
+        let rt_code = unsafe {
+            rsolace_sys::solClient_replicationGroupMessageId_fromString(
+                &mut rgmid,
+                Self::SIZE,
+                id.as_ptr(),
+            )
+        };
+        if rt_code != SolClientReturnCode::Ok as i32 {
+            return Err(invalid());
+        }
+        Ok(ReplicationGroupMsgId(rgmid))
+    }
+}
+
 impl SolMsg {
     pub fn new() -> Result<SolMsg, SolMsgError> {
         // 確保 Solace 庫已初始化（遵循官方文檔要求）
@@ -87,6 +180,8 @@ impl SolMsg {
         Ok(SolMsg {
             msg_p,
             user_prop_p: None,
+            #[cfg(feature = "channel")]
+            flow: None,
             // _ph: PhantomData,
             // container_p: None,
         })
@@ -117,13 +212,17 @@ impl SolMsg {
             SolClientReturnCode::Ok => Ok(SolMsg {
                 msg_p,
                 user_prop_p: Some(user_prop_p),
+                #[cfg(feature = "channel")]
+                flow: None,
                 // _ph: PhantomData,
             }),
             _ => {
                 Ok(SolMsg {
                     msg_p,
                     user_prop_p: None, //Some(user_prop_p)
-                                       // _ph: PhantomData,
+                    #[cfg(feature = "channel")]
+                    flow: None,
+                    // _ph: PhantomData,
                 })
             }
         }
@@ -298,6 +397,31 @@ impl SolMsg {
         }
     }
 
+    /// Attach an opaque correlation tag. The API hands it back, unchanged,
+    /// in the `Acknowledgement` / `RejectedMsgError` session event for this
+    /// message. Only the pointer value is stored, no memory is referenced.
+    pub fn set_correlation_tag(&mut self, tag: usize) -> SolClientReturnCode {
+        SolClientReturnCode::from_i32(unsafe {
+            rsolace_sys::solClient_msg_setCorrelationTagPtr(self.msg_p, tag as *mut c_void, 0)
+        })
+        .unwrap()
+    }
+
+    pub fn get_correlation_tag(&self) -> Result<usize, SolMsgError> {
+        let mut tag: *mut c_void = null_mut();
+        let mut size = 0;
+        let rt_code = unsafe {
+            rsolace_sys::solClient_msg_getCorrelationTagPtr(self.msg_p, &mut tag, &mut size)
+        };
+        ensure!(
+            rt_code == SolClientReturnCode::Ok as i32,
+            GetAttrSnafu {
+                attr: "correlation_tag"
+            }
+        );
+        Ok(tag as usize)
+    }
+
     pub fn set_class_of_service(&mut self, cos: u32) -> SolClientReturnCode {
         SolClientReturnCode::from_i32(unsafe {
             rsolace_sys::solClient_msg_setClassOfService(self.msg_p, cos - 1)
@@ -479,6 +603,141 @@ impl SolMsg {
         .unwrap()
     }
 
+    /// Guaranteed message id assigned by the broker, used to acknowledge
+    /// the message on its flow.
+    pub fn get_msg_id(&self) -> Result<u64, SolMsgError> {
+        let mut msg_id: rsolace_sys::solClient_msgId_t = 0;
+        let rt_code = unsafe { rsolace_sys::solClient_msg_getMsgId(self.msg_p, &mut msg_id) };
+        ensure!(
+            rt_code == SolClientReturnCode::Ok as i32,
+            GetAttrSnafu { attr: "msg_id" }
+        );
+        Ok(msg_id)
+    }
+
+    /// Replication group message id assigned by the broker to a received
+    /// guaranteed message.
+    pub fn get_replication_group_msg_id(&self) -> Result<ReplicationGroupMsgId, SolMsgError> {
+        let mut rgmid = rsolace_sys::solClient_replicationGroupMessageId {
+            replicationGroupMessageId: [0; 16],
+        };
+        let rt_code = unsafe {
+            rsolace_sys::solClient_msg_getReplicationGroupMessageId(
+                self.msg_p,
+                &mut rgmid,
+                ReplicationGroupMsgId::SIZE,
+            )
+        };
+        ensure!(
+            rt_code == SolClientReturnCode::Ok as i32,
+            GetAttrSnafu {
+                attr: "replication_group_msg_id"
+            }
+        );
+        Ok(ReplicationGroupMsgId(rgmid))
+    }
+
+    /// Whether the broker delivered this guaranteed message before.
+    pub fn is_redelivered(&self) -> bool {
+        unsafe { rsolace_sys::solClient_msg_isRedelivered(self.msg_p) == 1 }
+    }
+
+    /// Number of times the broker delivered this message, including this
+    /// delivery. Fails when the broker does not report delivery counts.
+    pub fn get_delivery_count(&self) -> Result<i32, SolMsgError> {
+        let mut count = 0;
+        let rt_code =
+            unsafe { rsolace_sys::solClient_msg_getDeliveryCount(self.msg_p, &mut count) };
+        ensure!(
+            rt_code == SolClientReturnCode::Ok as i32,
+            GetAttrSnafu {
+                attr: "delivery_count"
+            }
+        );
+        Ok(count)
+    }
+
+    /// Whether the message moves to the dead message queue when it expires
+    /// or exceeds the endpoint's max redelivery count.
+    pub fn set_dmq_eligible(&mut self, eligible: bool) -> SolClientReturnCode {
+        SolClientReturnCode::from_i32(unsafe {
+            rsolace_sys::solClient_msg_setDMQEligible(self.msg_p, eligible as u8)
+        })
+        .unwrap()
+    }
+
+    pub fn is_dmq_eligible(&self) -> bool {
+        unsafe { rsolace_sys::solClient_msg_isDMQEligible(self.msg_p) == 1 }
+    }
+
+    /// Time to live in milliseconds, 0 to never expire.
+    pub fn set_ttl(&mut self, ttl_ms: i64) -> SolClientReturnCode {
+        SolClientReturnCode::from_i32(unsafe {
+            rsolace_sys::solClient_msg_setTimeToLive(self.msg_p, ttl_ms)
+        })
+        .unwrap()
+    }
+
+    pub fn get_ttl(&self) -> Result<i64, SolMsgError> {
+        let mut ttl = 0;
+        let rt_code = unsafe { rsolace_sys::solClient_msg_getTimeToLive(self.msg_p, &mut ttl) };
+        ensure!(
+            rt_code == SolClientReturnCode::Ok as i32,
+            GetAttrSnafu { attr: "ttl" }
+        );
+        Ok(ttl)
+    }
+
+    /// Absolute expiration time in milliseconds since the epoch, 0 for
+    /// none. Set by the API from the TTL on send.
+    pub fn get_expiration(&self) -> Result<i64, SolMsgError> {
+        let mut ts = 0;
+        let rt_code = unsafe { rsolace_sys::solClient_msg_getExpiration(self.msg_p, &mut ts) };
+        ensure!(
+            rt_code == SolClientReturnCode::Ok as i32,
+            GetAttrSnafu { attr: "expiration" }
+        );
+        Ok(ts)
+    }
+
+    pub fn get_expiration_dt(&self) -> Result<DateTime<chrono::Utc>, SolMsgError> {
+        let ts = self.get_expiration()?;
+        DateTime::from_timestamp_millis(ts).context(GetAttrEmptySnafu { attr: "expiration" })
+    }
+
+    /// Copy of the message that can be modified and sent on its own, e.g.
+    /// to republish a received message to another destination.
+    pub fn dup(&self) -> Result<SolMsg, SolMsgError> {
+        let mut msg_p: rsolace_sys::solClient_opaqueMsg_pt = null_mut();
+        let rt_code = unsafe { rsolace_sys::solClient_msg_dup(self.msg_p, &mut msg_p) };
+        ensure!(
+            rt_code == SolClientReturnCode::Ok as i32,
+            AllocSnafu { msg_p }
+        );
+        unsafe { SolMsg::from_ptr(msg_p) }
+    }
+
+    #[cfg(feature = "channel")]
+    pub(crate) fn set_flow(&mut self, flow: Weak<SolFlowInner>) {
+        self.flow = Some(flow);
+    }
+
+    /// Acknowledge this message on the flow it was received from.
+    ///
+    /// Only needed when the flow was bound with
+    /// [`crate::solflow::FlowAckMode::Client`]; in auto mode the API acks
+    /// on its own. Fails if the message did not come from a flow or that
+    /// flow has already been dropped.
+    #[cfg(feature = "channel")]
+    pub fn ack(&self) -> Result<(), SolFlowError> {
+        let flow = self
+            .flow
+            .as_ref()
+            .and_then(|flow| flow.upgrade())
+            .ok_or(SolFlowError::FlowClosed)?;
+        flow.ack(self)
+    }
+
     pub fn get_cache_request_id(&self) -> Result<u64, SolMsgError> {
         let mut cache_req_id: u64 = 0;
         let rt_code =
@@ -974,6 +1233,8 @@ pub struct SolMsgBuilder {
     is_delivery_to_one: Option<bool>,
     user_props: Vec<(String, String)>,
     binary_attachment: Option<Vec<u8>>,
+    dmq_eligible: Option<bool>,
+    ttl_ms: Option<i64>,
 }
 
 impl Default for SolMsgBuilder {
@@ -989,6 +1250,8 @@ impl Default for SolMsgBuilder {
             is_delivery_to_one: None,
             user_props: Vec::new(),
             binary_attachment: None,
+            dmq_eligible: None,
+            ttl_ms: None,
         }
     }
 }
@@ -1057,6 +1320,16 @@ impl SolMsgBuilder {
         self
     }
 
+    pub fn as_dmq_eligible(mut self, dmq_eligible: bool) -> Self {
+        self.dmq_eligible = Some(dmq_eligible);
+        self
+    }
+
+    pub fn with_ttl(mut self, ttl_ms: i64) -> Self {
+        self.ttl_ms = Some(ttl_ms);
+        self
+    }
+
     pub fn build(self) -> Result<SolMsg, SolMsgError> {
         let mut m = SolMsg::new()?;
         m.set_delivery_mode(self.delivery_mode);
@@ -1087,6 +1360,12 @@ impl SolMsgBuilder {
         if let Some(binary_attachment) = self.binary_attachment {
             m.set_binary_attachment(&binary_attachment);
         }
+        if let Some(dmq_eligible) = self.dmq_eligible {
+            m.set_dmq_eligible(dmq_eligible);
+        }
+        if let Some(ttl_ms) = self.ttl_ms {
+            m.set_ttl(ttl_ms);
+        }
         Ok(m)
     }
 }
@@ -1099,7 +1378,7 @@ mod tests {
         SolClientCacheStatus, SolClientDeliveryMode, SolClientDestType, SolClientReturnCode,
     };
 
-    use super::{Destination, SolMsg, SolMsgBuilder, SolMsgError};
+    use super::{Destination, ReplicationGroupMsgId, SolMsg, SolMsgBuilder, SolMsgError};
 
     use rstest::{fixture, rstest};
 
@@ -1319,6 +1598,55 @@ mod tests {
         assert_eq!(solmsg.get_binary_attachment().unwrap(), vec![0, 1, 2, 3, 4]);
     }
 
+    #[rstest]
+    fn solmsg_replication_group_msg_id(solmsg: SolMsg) {
+        // only set on guaranteed messages received from a broker
+        assert!(solmsg.get_replication_group_msg_id().is_err());
+        assert_eq!(
+            "not-an-rgmid".parse::<ReplicationGroupMsgId>(),
+            Err(SolMsgError::InvalidReplicationGroupMsgId {
+                id: "not-an-rgmid".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn solmsg_replication_group_msg_id_round_trip() {
+        let first = "rmid1:0d77c-b0b2e66aece-00000000-00000001";
+        let second = "rmid1:0d77c-b0b2e66aece-00000000-00000002";
+        let first_id: ReplicationGroupMsgId = first.parse().unwrap();
+        let second_id: ReplicationGroupMsgId = second.parse().unwrap();
+        assert_eq!(first_id.to_string(), first);
+        assert_eq!(second_id.to_string(), second);
+        assert_eq!(first_id, first.parse().unwrap());
+        assert_eq!(
+            first_id.partial_cmp(&second_id),
+            Some(std::cmp::Ordering::Less)
+        );
+        assert!(second_id > first_id);
+        assert_eq!(
+            first_id.partial_cmp(&first_id),
+            Some(std::cmp::Ordering::Equal)
+        );
+    }
+
+    #[test]
+    fn solmsg_redelivery_attrs() {
+        let msg = SolMsgBuilder::new()
+            .with_delivery_mode(SolClientDeliveryMode::Persistent)
+            .as_dmq_eligible(true)
+            .with_ttl(30_000)
+            .build()
+            .unwrap();
+        assert!(msg.is_dmq_eligible());
+        assert_eq!(msg.get_ttl().unwrap(), 30_000);
+        assert!(!msg.is_redelivered());
+
+        let dup = msg.dup().unwrap();
+        assert!(dup.is_dmq_eligible());
+        assert_eq!(dup.get_ttl().unwrap(), 30_000);
+    }
+
     #[test]
     fn solmsg_send_between_threads() {
         use std::sync::mpsc;
@@ -6,6 +6,8 @@
 use std::ffi::CString;
 use std::os::raw::c_char;
 use std::ptr::null;
+#[cfg(all(feature = "channel", feature = "tokio"))]
+use std::time::Duration;
 
 use crate::utils::ConvertToCString;
 
@@ -211,6 +213,34 @@ impl SessionProps {
         props
     }
 
+    /// Longest time connecting can take. Each of the `connect_retries + 1`
+    /// rounds tries every host of the host list once, every attempt lasts
+    /// up to `connect_timeout_ms` and is followed by a
+    /// `reconnect_retry_wait_ms` pause, except the last one. One second is
+    /// added for the failure event to arrive. `None` when the session
+    /// retries forever.
+    #[cfg(all(feature = "channel", feature = "tokio"))]
+    pub(crate) fn connect_wait(&self) -> Option<Duration> {
+        let parse = |v: &CString| v.to_str().ok()?.parse::<i64>().ok();
+        let timeout_ms = parse(&self.connect_timeout_ms)?;
+        let retries = parse(&self.connect_retries)?;
+        let retry_wait_ms = parse(&self.reconnect_retry_wait_ms)?;
+        if retries < 0 {
+            return None;
+        }
+        let hosts = self
+            .host
+            .to_str()
+            .ok()?
+            .split(',')
+            .filter(|host| !host.trim().is_empty())
+            .count()
+            .max(1) as i64;
+        let attempts = (retries + 1) * hosts;
+        let wait_ms = attempts * timeout_ms + (attempts - 1) * retry_wait_ms + 1000;
+        Some(Duration::from_millis(wait_ms as u64))
+    }
+
     // === Required field builder methods ===
 
     pub fn username(mut self, username: &str) -> Self {
@@ -484,6 +514,23 @@ mod tests {
         assert!(c_props.last().unwrap().is_null());
     }
 
+    #[cfg(all(feature = "channel", feature = "tokio"))]
+    #[test]
+    fn test_connect_wait() {
+        let props = SessionProps::default()
+            .host("tcp://a:55555")
+            .connect_timeout_ms(2000)
+            .reconnect_retry_wait_ms(500);
+        assert_eq!(props.connect_wait(), Some(Duration::from_millis(3000)));
+        // 3 rounds: 3 attempts and 2 pauses
+        let props = props.connect_retries(2);
+        assert_eq!(props.connect_wait(), Some(Duration::from_millis(8000)));
+        // 2 hosts: 6 attempts and 5 pauses
+        let props = props.host("tcp://a:55555, tcp://b:55555");
+        assert_eq!(props.connect_wait(), Some(Duration::from_millis(15500)));
+        assert_eq!(props.connect_retries(-1).connect_wait(), None);
+    }
+
     #[test]
     fn test_ssl_trust_store_dir_none() {
         let props = SessionProps::default().ssl_trust_store_dir(None);
@@ -0,0 +1,183 @@
+//! Windowed guaranteed publishing.
+//!
+//! [`GuaranteedPublisher`] wraps [`SolClient::send_persistent`] with an
+//! in-flight window: at most `window` messages may be awaiting a broker
+//! ack at any time. When the window is full, or the session reports
+//! `WouldBlock`, publishing waits instead of failing.
+
+use super::solclient::{PersistentReceipt, SolClient, SolClientError};
+use super::solmsg::SolMsg;
+use super::types::SolClientReturnCode;
+use kanal::{bounded, Receiver, Sender};
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long a sender blocked on `WouldBlock` waits for `CanSend` before
+/// trying again anyway. Guards against a notification taken by another
+/// publisher on the same session.
+const CAN_SEND_RETRY_MS: u64 = 100;
+
+/// One slot of a publisher's in-flight window. Returned to the window
+/// when dropped.
+pub(crate) struct InFlightPermit {
+    release: Sender<()>,
+}
+
+impl Drop for InFlightPermit {
+    fn drop(&mut self) {
+        // The window channel has room for every permit, so this never fails
+        // unless the publisher itself is gone.
+        let _ = self.release.try_send(());
+    }
+}
+
+/// Guaranteed publisher with a bounded number of unacknowledged messages.
+///
+/// ```ignore
+/// let client = client.into_shared();
+/// let publisher = GuaranteedPublisher::new(client.clone(), NonZeroUsize::new(64).unwrap());
+/// let receipt = publisher.publish(&mut msg).await?;
+/// receipt.confirmed().await?;
+/// ```
+pub struct GuaranteedPublisher {
+    client: Arc<SolClient>,
+    window: usize,
+    permit_sender: Sender<()>,
+    permit_receiver: Receiver<()>,
+    can_send: Receiver<()>,
+}
+
+impl GuaranteedPublisher {
+    /// Create a publisher allowing up to `window` unacknowledged messages.
+    pub fn new(client: Arc<SolClient>, window: NonZeroUsize) -> Self {
+        let window = window.get();
+        let (permit_sender, permit_receiver) = bounded(window);
+        for _ in 0..window {
+            permit_sender.send(()).unwrap();
+        }
+        let can_send = client.get_can_send_receiver();
+        Self {
+            client,
+            window,
+            permit_sender,
+            permit_receiver,
+            can_send,
+        }
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Number of messages sent but not yet acked or rejected by the broker.
+    pub fn in_flight(&self) -> usize {
+        self.window - self.permit_receiver.len()
+    }
+
+    /// Send `msg` once a window slot is free, blocking the calling thread
+    /// while the window is full or the session reports `WouldBlock`.
+    ///
+    /// Returns once the API accepted the message; use the receipt to wait
+    /// for the broker outcome.
+    pub fn publish_blocking(&self, msg: &mut SolMsg) -> Result<PersistentReceipt, SolClientError> {
+        self.permit_receiver
+            .recv()
+            .map_err(|_| SolClientError::PublisherClosed)?;
+        let mut permit = self.permit();
+        loop {
+            match self.client.send_persistent_with_permit(msg, Some(permit)) {
+                Ok(receipt) => return Ok(receipt),
+                Err((e, returned)) if is_would_block(&e) => {
+                    permit = returned.unwrap();
+                    let _ = self
+                        .can_send
+                        .recv_timeout(Duration::from_millis(CAN_SEND_RETRY_MS));
+                }
+                Err((e, _)) => return Err(e),
+            }
+        }
+    }
+
+    /// Async variant of [`GuaranteedPublisher::publish_blocking`]: waits
+    /// for a window slot and for `CanSend` without blocking the runtime.
+    #[cfg(feature = "tokio")]
+    pub async fn publish(&self, msg: &mut SolMsg) -> Result<PersistentReceipt, SolClientError> {
+        self.permit_receiver
+            .as_async()
+            .recv()
+            .await
+            .map_err(|_| SolClientError::PublisherClosed)?;
+        let mut permit = self.permit();
+        loop {
+            match self.client.send_persistent_with_permit(msg, Some(permit)) {
+                Ok(receipt) => return Ok(receipt),
+                Err((e, returned)) if is_would_block(&e) => {
+                    permit = returned.unwrap();
+                    let _ = tokio::time::timeout(
+                        Duration::from_millis(CAN_SEND_RETRY_MS),
+                        self.can_send.as_async().recv(),
+                    )
+                    .await;
+                }
+                Err((e, _)) => return Err(e),
+            }
+        }
+    }
+
+    fn permit(&self) -> InFlightPermit {
+        InFlightPermit {
+            release: self.permit_sender.clone(),
+        }
+    }
+}
+
+fn is_would_block(e: &SolClientError) -> bool {
+    matches!(
+        e,
+        SolClientError::SendPersistent {
+            code: SolClientReturnCode::WouldBlock,
+            ..
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SolClientLogLevel;
+
+    #[test]
+    fn test_permit_returns_to_window_on_drop() {
+        let publisher = GuaranteedPublisher::new(
+            SolClient::new(SolClientLogLevel::Notice)
+                .unwrap()
+                .into_shared(),
+            NonZeroUsize::new(2).unwrap(),
+        );
+        assert_eq!(publisher.in_flight(), 0);
+
+        publisher.permit_receiver.recv().unwrap();
+        let permit = publisher.permit();
+        assert_eq!(publisher.in_flight(), 1);
+
+        drop(permit);
+        assert_eq!(publisher.in_flight(), 0);
+    }
+    #[test]
+    fn test_closed_window_returns_error() {
+        let publisher = GuaranteedPublisher::new(
+            SolClient::new(SolClientLogLevel::Notice)
+                .unwrap()
+                .into_shared(),
+            NonZeroUsize::new(1).unwrap(),
+        );
+        publisher.permit_receiver.close();
+
+        let mut msg = SolMsg::new().unwrap();
+        assert!(matches!(
+            publisher.publish_blocking(&mut msg),
+            Err(SolClientError::PublisherClosed)
+        ));
+    }
+}
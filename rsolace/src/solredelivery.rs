@@ -0,0 +1,250 @@
+//! Poison-message handling for guaranteed consumers.
+//!
+//! The broker redelivers a message that is never acknowledged until the
+//! endpoint's max redelivery count is reached, which is forever by
+//! default. [`RedeliveryGuard`] wraps a flow's receiver and takes a message
+//! out of that loop once it was delivered more than `max_deliveries` times:
+//! the message goes to a handler or a parking topic and is then
+//! acknowledged.
+//!
+//! Relies on [`SolMsg::get_delivery_count`], so the broker must report
+//! delivery counts, and on a flow bound with
+//! [`crate::solflow::FlowAckMode::Client`].
+
+use super::solclient::{SolClient, SolClientError};
+use super::solflow::{SolFlow, SolFlowError};
+use super::solmsg::{SolMsg, SolMsgError};
+use kanal::Receiver;
+use snafu::prelude::{ensure, Snafu};
+use snafu::ResultExt;
+use std::sync::Arc;
+
+#[derive(Debug, Snafu, PartialEq)]
+pub enum RedeliveryError {
+    #[snafu(display("RedeliveryPolicy max_deliveries must be at least 1"))]
+    NoDeliveries,
+    #[snafu(display("RedeliveryGuard flow receiver closed"))]
+    Closed,
+    #[snafu(display("RedeliveryGuard copy msg to park {}", source))]
+    Copy { source: SolMsgError },
+    #[snafu(display("RedeliveryGuard park msg to {topic} {}", source))]
+    Park {
+        topic: String,
+        source: SolClientError,
+    },
+    #[snafu(display("RedeliveryGuard ack poison msg {}", source))]
+    Ack { source: SolFlowError },
+}
+
+/// What happens to a message delivered too many times.
+pub enum PoisonAction {
+    /// Call the handler with the message, then acknowledge it.
+    Handler(Box<dyn Fn(&SolMsg) + Send + Sync>),
+    /// Republish a copy to `topic` as a persistent message and acknowledge
+    /// the original once the broker accepted the copy.
+    Park {
+        client: Arc<SolClient>,
+        topic: String,
+    },
+}
+
+/// Decides when a message is poison and what to do with it.
+///
+/// ```ignore
+/// let policy = RedeliveryPolicy::park(5, client.clone(), "orders/parked")?;
+/// let guard = RedeliveryGuard::new(&flow, policy);
+/// while let Ok(msg) = guard.recv() {
+///     process(&msg)?;
+///     msg.ack()?;
+/// }
+/// ```
+pub struct RedeliveryPolicy {
+    max_deliveries: u32,
+    action: PoisonAction,
+}
+
+impl RedeliveryPolicy {
+    /// A message is processed at most `max_deliveries` times, which must
+    /// be at least 1.
+    pub fn new(max_deliveries: u32, action: PoisonAction) -> Result<Self, RedeliveryError> {
+        ensure!(max_deliveries > 0, NoDeliveriesSnafu);
+        Ok(Self {
+            max_deliveries,
+            action,
+        })
+    }
+
+    /// Hand messages delivered more than `max_deliveries` times to
+    /// `handler`.
+    pub fn handler<F>(max_deliveries: u32, handler: F) -> Result<Self, RedeliveryError>
+    where
+        F: Fn(&SolMsg) + Send + Sync + 'static,
+    {
+        Self::new(max_deliveries, PoisonAction::Handler(Box::new(handler)))
+    }
+
+    /// Republish messages delivered more than `max_deliveries` times to
+    /// `topic`.
+    pub fn park(
+        max_deliveries: u32,
+        client: Arc<SolClient>,
+        topic: &str,
+    ) -> Result<Self, RedeliveryError> {
+        Self::new(
+            max_deliveries,
+            PoisonAction::Park {
+                client,
+                topic: topic.to_string(),
+            },
+        )
+    }
+
+    pub fn max_deliveries(&self) -> u32 {
+        self.max_deliveries
+    }
+
+    /// Whether `msg` was delivered more than `max_deliveries` times.
+    /// Messages without a delivery count are never poison.
+    pub fn is_poison(&self, msg: &SolMsg) -> bool {
+        msg.get_delivery_count()
+            .is_ok_and(|count| self.is_poison_count(count))
+    }
+
+    /// Whether the `count`th delivery of a message is past the limit: the
+    /// first `max_deliveries` deliveries are processed.
+    fn is_poison_count(&self, count: i32) -> bool {
+        i64::from(count) > i64::from(self.max_deliveries)
+    }
+
+    /// Apply the policy's action to `msg` and acknowledge it. `msg` is left
+    /// unacknowledged when the action fails.
+    pub fn settle(&self, msg: &SolMsg) -> Result<(), RedeliveryError> {
+        match &self.action {
+            PoisonAction::Handler(handler) => handler(msg),
+            PoisonAction::Park { client, topic } => {
+                let mut parked = park_copy(msg, topic)?;
+                client
+                    .send_persistent(&mut parked)
+                    .and_then(|receipt| receipt.wait())
+                    .context(ParkSnafu { topic })?;
+            }
+        }
+        msg.ack().context(AckSnafu)
+    }
+
+    /// Async variant of [`RedeliveryPolicy::settle`].
+    #[cfg(feature = "tokio")]
+    pub async fn settle_async(&self, msg: &SolMsg) -> Result<(), RedeliveryError> {
+        match &self.action {
+            PoisonAction::Handler(handler) => handler(msg),
+            PoisonAction::Park { client, topic } => {
+                let mut parked = park_copy(msg, topic)?;
+                let receipt = client
+                    .send_persistent(&mut parked)
+                    .context(ParkSnafu { topic })?;
+                receipt.confirmed().await.context(ParkSnafu { topic })?;
+            }
+        }
+        msg.ack().context(AckSnafu)
+    }
+}
+
+fn park_copy(msg: &SolMsg, topic: &str) -> Result<SolMsg, RedeliveryError> {
+    let mut parked = msg.dup().context(CopySnafu)?;
+    parked.set_topic(topic);
+    Ok(parked)
+}
+
+/// A flow receiver that settles poison messages with a
+/// [`RedeliveryPolicy`] and only returns the others.
+pub struct RedeliveryGuard {
+    receiver: Receiver<SolMsg>,
+    policy: RedeliveryPolicy,
+}
+
+impl RedeliveryGuard {
+    pub fn new(flow: &SolFlow, policy: RedeliveryPolicy) -> Self {
+        Self {
+            receiver: flow.get_msg_receiver(),
+            policy,
+        }
+    }
+
+    /// Next message that is not poison. Fails when settling a poison
+    /// message fails; that message is then redelivered later.
+    pub fn recv(&self) -> Result<SolMsg, RedeliveryError> {
+        loop {
+            let msg = self.receiver.recv().map_err(|_| RedeliveryError::Closed)?;
+            if !self.policy.is_poison(&msg) {
+                return Ok(msg);
+            }
+            self.policy.settle(&msg)?;
+        }
+    }
+
+    /// Async variant of [`RedeliveryGuard::recv`].
+    #[cfg(feature = "tokio")]
+    pub async fn recv_async(&self) -> Result<SolMsg, RedeliveryError> {
+        loop {
+            let msg = self
+                .receiver
+                .as_async()
+                .recv()
+                .await
+                .map_err(|_| RedeliveryError::Closed)?;
+            if !self.policy.is_poison(&msg) {
+                return Ok(msg);
+            }
+            self.policy.settle_async(&msg).await?;
+        }
+    }
+
+    pub fn policy(&self) -> &RedeliveryPolicy {
+        &self.policy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_msg_without_delivery_count_is_not_poison() {
+        let policy = RedeliveryPolicy::handler(1, |_| {}).unwrap();
+        let msg = SolMsg::new().unwrap();
+        assert!(!policy.is_poison(&msg));
+    }
+
+    #[test]
+    fn test_poison_after_max_deliveries() {
+        assert_eq!(
+            RedeliveryPolicy::handler(0, |_| {}).err(),
+            Some(RedeliveryError::NoDeliveries)
+        );
+        let once = RedeliveryPolicy::handler(1, |_| {}).unwrap();
+        assert!(!once.is_poison_count(1));
+        assert!(once.is_poison_count(2));
+        let thrice = RedeliveryPolicy::handler(3, |_| {}).unwrap();
+        let poison: Vec<bool> = (1..=5).map(|count| thrice.is_poison_count(count)).collect();
+        assert_eq!(poison, vec![false, false, false, true, true]);
+    }
+
+    #[test]
+    fn test_settle_handler_needs_flow_to_ack() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = calls.clone();
+        let policy = RedeliveryPolicy::handler(3, move |_| {
+            counted.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap();
+        let msg = SolMsg::new().unwrap();
+        assert_eq!(
+            policy.settle(&msg),
+            Err(RedeliveryError::Ack {
+                source: SolFlowError::FlowClosed
+            })
+        );
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+}
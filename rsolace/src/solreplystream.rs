@@ -0,0 +1,358 @@
+//! Requests answered by a stream of replies.
+//!
+//! A service can answer one request with several replies carrying the
+//! request's correlation id, e.g. one per page of a query, marking the last
+//! one with the [`END_OF_STREAM_PROP`] user property. [`ReplyStream`]
+//! yields them in order as they arrive and ends after the marked reply, or
+//! with [`SolClientError::RequestTimeout`] when the next reply takes longer
+//! than the idle timeout.
+//!
+//! Scatter-gather requests reuse the same plumbing: several responders
+//! answer one request and the requester keeps the replies that arrived
+//! before a deadline, each as a [`GatheredReply`]. There the marker ends
+//! nothing, as one responder's last reply is not the others'.
+
+use super::solclient::SolClientError;
+use super::solmsg::SolMsg;
+use super::types::SolClientReturnCode;
+use dashmap::DashMap;
+use futures_core::Stream;
+use kanal::{unbounded_async, AsyncReceiver, AsyncSender, ReceiveError};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// User property marking the last reply of a stream. Any value counts.
+pub const END_OF_STREAM_PROP: &str = "rsolace_eos";
+
+/// Mark `reply` as the last reply of its stream.
+pub fn set_end_of_stream(reply: &mut SolMsg) -> SolClientReturnCode {
+    reply.set_user_prop(END_OF_STREAM_PROP, "1", 24)
+}
+
+/// Whether `reply` is the last reply of its stream.
+pub fn is_end_of_stream(reply: &SolMsg) -> bool {
+    reply.get_user_prop(END_OF_STREAM_PROP).is_ok()
+}
+
+/// Entry of a request answered by several replies, registered until its
+/// [`ReplyStream`] is dropped.
+pub(crate) struct StreamReplies {
+    pub(crate) sender: AsyncSender<SolMsg>,
+    /// Whether a reply marked with [`END_OF_STREAM_PROP`] ends the stream.
+    pub(crate) ends_on_marker: bool,
+    /// Unique per request, tells its entry apart from a later request
+    /// reusing the correlation id.
+    pub(crate) tag: usize,
+}
+
+/// [`StreamReplies`] keyed by correlation id, shared by the client and its
+/// reply streams.
+pub(crate) type StreamReplyMap = Arc<DashMap<String, StreamReplies>>;
+
+/// One reply collected by
+/// [`crate::solclient::SolClient::send_request_gather`].
+#[derive(Debug)]
+pub struct GatheredReply {
+    /// Sender id of the responder, `None` when the reply carries none.
+    /// Responders set it with `SessionProps::generate_sender_id(true)` or
+    /// `SolMsg::set_sender_id`.
+    pub sender_id: Option<String>,
+    pub reply: SolMsg,
+}
+
+impl GatheredReply {
+    fn new(reply: SolMsg) -> Self {
+        Self {
+            sender_id: reply.get_sender_id().ok(),
+            reply,
+        }
+    }
+}
+
+type NextReply = Pin<Box<dyn Future<Output = Option<Result<SolMsg, ReceiveError>>> + Send>>;
+
+/// Replies to a request sent with
+/// [`crate::solclient::SolClient::send_request_stream`].
+///
+/// ```ignore
+/// let mut pages = client.send_request_stream(&query, Duration::from_secs(5))?;
+/// while let Some(page) = pages.next_reply().await {
+///     render(&page?);
+/// }
+/// ```
+///
+/// Dropping it stops listening: later replies are discarded. It does not
+/// borrow the client, so it can be moved into a spawned task.
+pub struct ReplyStream {
+    topic: String,
+    corrid: String,
+    tag: usize,
+    idle_timeout: Duration,
+    ends_on_marker: bool,
+    receiver: AsyncReceiver<SolMsg>,
+    map: StreamReplyMap,
+    next: Option<NextReply>,
+    done: bool,
+}
+
+impl ReplyStream {
+    /// Register the request `corrid` in `map` and listen to its replies.
+    /// An older request with the same correlation id is replaced.
+    pub(crate) fn register(
+        topic: String,
+        corrid: String,
+        tag: usize,
+        idle_timeout: Duration,
+        ends_on_marker: bool,
+        map: StreamReplyMap,
+    ) -> Self {
+        let (sender, receiver) = unbounded_async();
+        let entry = StreamReplies {
+            sender,
+            ends_on_marker,
+            tag,
+        };
+        if map.insert(corrid.clone(), entry).is_some() {
+            tracing::warn!("pending request with correlation id {} replaced", corrid);
+        }
+        Self {
+            topic,
+            corrid,
+            tag,
+            idle_timeout,
+            ends_on_marker,
+            receiver,
+            map,
+            next: None,
+            done: false,
+        }
+    }
+
+    /// Correlation id the replies carry.
+    pub fn correlation_id(&self) -> &str {
+        &self.corrid
+    }
+
+    /// Next reply, `None` once the stream ended.
+    pub async fn next_reply(&mut self) -> Option<Result<SolMsg, SolClientError>> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+
+    /// Collect replies until `max_replies` arrived or `timeout` elapsed,
+    /// in arrival order. The deadline is not an error: the replies so far
+    /// are returned, possibly none.
+    pub(crate) async fn gather(
+        mut self,
+        max_replies: usize,
+        timeout: Duration,
+    ) -> Result<Vec<GatheredReply>, SolClientError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut replies = Vec::new();
+        while replies.len() < max_replies {
+            match tokio::time::timeout_at(deadline, self.next_reply()).await {
+                Ok(Some(Ok(reply))) => replies.push(GatheredReply::new(reply)),
+                Ok(Some(Err(SolClientError::RequestTimeout { .. }))) | Ok(None) | Err(_) => break,
+                Ok(Some(Err(e))) => return Err(e),
+            }
+        }
+        Ok(replies)
+    }
+
+    fn finish(&mut self) {
+        self.done = true;
+        self.next = None;
+        self.forget();
+    }
+
+    /// Remove this request's entry, unless a later request replaced it.
+    fn forget(&self) {
+        self.map
+            .remove_if(&self.corrid, |_, entry| entry.tag == self.tag);
+    }
+}
+
+impl Stream for ReplyStream {
+    type Item = Result<SolMsg, SolClientError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+        if this.next.is_none() {
+            let receiver = this.receiver.clone();
+            let idle_timeout = this.idle_timeout;
+            this.next = Some(Box::pin(async move {
+                tokio::time::timeout(idle_timeout, receiver.recv())
+                    .await
+                    .ok()
+            }));
+        }
+        let outcome = match this.next.as_mut().unwrap().as_mut().poll(cx) {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(outcome) => outcome,
+        };
+        this.next = None;
+        let item = match outcome {
+            Some(Ok(reply)) => {
+                if this.ends_on_marker && is_end_of_stream(&reply) {
+                    this.finish();
+                }
+                Ok(reply)
+            }
+            None => {
+                this.finish();
+                Err(SolClientError::RequestTimeout {
+                    topic: this.topic.clone(),
+                    timeout: this.idle_timeout,
+                })
+            }
+            // Only destroying the session closes the channel early.
+            Some(Err(_)) => {
+                this.finish();
+                Err(SolClientError::SessionDestroyed {
+                    topic: this.topic.clone(),
+                })
+            }
+        };
+        Poll::Ready(Some(item))
+    }
+}
+
+impl Drop for ReplyStream {
+    fn drop(&mut self) {
+        self.forget();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sender_of(map: &StreamReplyMap, corrid: &str) -> AsyncSender<SolMsg> {
+        map.get(corrid).unwrap().sender.clone()
+    }
+
+    #[test]
+    fn test_end_of_stream_marker() {
+        let mut reply = SolMsg::new().unwrap();
+        assert!(!is_end_of_stream(&reply));
+        set_end_of_stream(&mut reply);
+        assert!(is_end_of_stream(&reply));
+    }
+
+    #[tokio::test]
+    async fn test_reply_stream_ends_on_marker_and_idle() {
+        let map = StreamReplyMap::default();
+        let mut stream = ReplyStream::register(
+            "api/pages".to_string(),
+            "c1".to_string(),
+            1,
+            Duration::from_millis(50),
+            true,
+            map.clone(),
+        );
+        let sender = sender_of(&map, "c1");
+        sender.try_send(SolMsg::new().unwrap()).unwrap();
+        let mut last = SolMsg::new().unwrap();
+        set_end_of_stream(&mut last);
+        sender.try_send(last).unwrap();
+        assert!(stream.next_reply().await.unwrap().is_ok());
+        assert!(stream.next_reply().await.unwrap().is_ok());
+        assert!(stream.next_reply().await.is_none());
+        assert!(map.is_empty());
+
+        let mut idle = ReplyStream::register(
+            "api/pages".to_string(),
+            "c2".to_string(),
+            2,
+            Duration::from_millis(10),
+            true,
+            map.clone(),
+        );
+        assert!(matches!(
+            idle.next_reply().await,
+            Some(Err(SolClientError::RequestTimeout { .. }))
+        ));
+        assert!(idle.next_reply().await.is_none());
+        assert!(map.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dropped_stream_leaves_newer_request() {
+        let map = StreamReplyMap::default();
+        let old = ReplyStream::register(
+            "api/pages".to_string(),
+            "reused".to_string(),
+            1,
+            Duration::from_millis(50),
+            true,
+            map.clone(),
+        );
+        let mut new = ReplyStream::register(
+            "api/pages".to_string(),
+            "reused".to_string(),
+            2,
+            Duration::from_millis(50),
+            true,
+            map.clone(),
+        );
+        drop(old);
+        assert_eq!(map.get("reused").unwrap().tag, 2);
+
+        // owns its map, so it can move into a spawned task
+        let sender = sender_of(&map, "reused");
+        let task = tokio::spawn(async move { new.next_reply().await.unwrap().is_ok() });
+        sender.try_send(SolMsg::new().unwrap()).unwrap();
+        assert!(task.await.unwrap());
+        assert!(map.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_gather_stops_at_count_or_deadline() {
+        let map = StreamReplyMap::default();
+        let stream = ReplyStream::register(
+            "api/positions".to_string(),
+            "g1".to_string(),
+            1,
+            Duration::from_millis(50),
+            false,
+            map.clone(),
+        );
+        let sender = sender_of(&map, "g1");
+        for shard in ["shard-1", "shard-2"] {
+            let mut reply = SolMsg::new().unwrap();
+            reply.set_sender_id(shard);
+            // one responder's last reply does not end the gather
+            set_end_of_stream(&mut reply);
+            sender.try_send(reply).unwrap();
+        }
+        sender.try_send(SolMsg::new().unwrap()).unwrap();
+        let replies = stream.gather(2, Duration::from_millis(50)).await.unwrap();
+        let senders: Vec<_> = replies.iter().map(|r| r.sender_id.clone()).collect();
+        assert_eq!(
+            senders,
+            vec![Some("shard-1".to_string()), Some("shard-2".to_string())]
+        );
+        assert!(map.is_empty());
+
+        let stream = ReplyStream::register(
+            "api/positions".to_string(),
+            "g2".to_string(),
+            2,
+            Duration::from_millis(20),
+            false,
+            map.clone(),
+        );
+        sender_of(&map, "g2")
+            .try_send(SolMsg::new().unwrap())
+            .unwrap();
+        let replies = stream.gather(3, Duration::from_millis(20)).await.unwrap();
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0].sender_id, None);
+        assert!(map.is_empty());
+    }
+}
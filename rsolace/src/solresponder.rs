@@ -0,0 +1,339 @@
+//! Request/reply servers.
+//!
+//! A [`Responder`] routes requests to async handlers by topic pattern and
+//! replies with whatever the handler returns. The patterns are subscribed
+//! with per-subscription dispatch, so the API does the routing, wildcards
+//! included, and the requests never reach the client's own receivers.
+//! Handlers run as tokio tasks, at most `max_concurrency` at a time.
+//!
+//! A failed handler is answered with an error envelope: a reply carrying
+//! the error code in the [`ERROR_CODE_PROP`] user property and the message
+//! as binary attachment. Requesters decode it with
+//! [`HandlerError::from_reply`].
+
+use super::solclient::{SolClient, SolClientError};
+use super::soldispatch::TopicDispatch;
+use super::solmsg::{SolMsg, SolMsgError};
+use super::types::SolClientReturnCode;
+use kanal::{bounded, Receiver};
+use snafu::prelude::{ensure, Snafu};
+use snafu::ResultExt;
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// User property holding the error code of an error envelope.
+pub const ERROR_CODE_PROP: &str = "rsolace_error_code";
+
+/// Error code of the envelope sent when a handler panicked.
+pub const INTERNAL_ERROR: &str = "internal";
+
+/// Size of the user property map of replies.
+const USER_PROP_MAP_SIZE: u32 = 24;
+
+#[derive(Debug, Snafu, PartialEq)]
+pub enum ResponderError {
+    #[snafu(display("Responder without handlers"))]
+    NoHandlers,
+    #[snafu(display("Responder subscribe {pattern} {}", source))]
+    Subscribe {
+        pattern: String,
+        source: SolClientError,
+    },
+}
+
+/// Failure of a handler, sent back to the requester as error envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandlerError {
+    pub code: String,
+    pub message: String,
+}
+
+impl HandlerError {
+    pub fn new(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            code: code.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// The error a reply carries when it is an error envelope.
+    pub fn from_reply(reply: &SolMsg) -> Option<HandlerError> {
+        let code = reply.get_user_prop(ERROR_CODE_PROP).ok()?;
+        let message = reply
+            .get_binary_attachment()
+            .map(|data| String::from_utf8_lossy(&data).into_owned())
+            .unwrap_or_default();
+        Some(HandlerError { code, message })
+    }
+}
+
+impl std::fmt::Display for HandlerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for HandlerError {}
+
+/// Successful result of a handler.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Reply {
+    payload: Vec<u8>,
+    user_props: Vec<(String, String)>,
+}
+
+impl Reply {
+    pub fn new(payload: impl Into<Vec<u8>>) -> Self {
+        Self {
+            payload: payload.into(),
+            user_props: Vec::new(),
+        }
+    }
+
+    pub fn with_user_prop(mut self, key: &str, value: &str) -> Self {
+        self.user_props.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+}
+
+impl From<Vec<u8>> for Reply {
+    fn from(payload: Vec<u8>) -> Self {
+        Reply::new(payload)
+    }
+}
+
+impl From<String> for Reply {
+    fn from(payload: String) -> Self {
+        Reply::new(payload)
+    }
+}
+
+impl From<&str> for Reply {
+    fn from(payload: &str) -> Self {
+        Reply::new(payload)
+    }
+}
+
+type HandlerFuture = Pin<Box<dyn Future<Output = Result<Reply, HandlerError>> + Send>>;
+type Handler = Arc<dyn Fn(SolMsg) -> HandlerFuture + Send + Sync>;
+
+struct Route {
+    pattern: String,
+    handler: Handler,
+}
+
+/// Serves requests with async handlers registered per topic pattern.
+///
+/// ```ignore
+/// let client = Arc::new(client);
+/// Responder::new(client.clone())
+///     .max_concurrency(NonZeroUsize::new(32).unwrap())
+///     .handle("api/v1/orders/>", |req: SolMsg| async move {
+///         let order = parse(&req).map_err(|e| HandlerError::new("bad_request", e.to_string()))?;
+///         Ok(serialize(&book(order).await))
+///     })
+///     .run()
+///     .await?;
+/// ```
+pub struct Responder {
+    client: Arc<SolClient>,
+    routes: Vec<Route>,
+    max_concurrency: usize,
+    queue_capacity: usize,
+}
+
+impl Responder {
+    pub fn new(client: Arc<SolClient>) -> Self {
+        Self {
+            client,
+            routes: Vec::new(),
+            max_concurrency: 16,
+            queue_capacity: 1024,
+        }
+    }
+
+    /// Answer requests published to topics matching `pattern` with
+    /// `handler`. A request matching several patterns goes to each of
+    /// their handlers, so patterns should not overlap.
+    pub fn handle<F, Fut, R>(mut self, pattern: &str, handler: F) -> Self
+    where
+        F: Fn(SolMsg) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, HandlerError>> + Send + 'static,
+        R: Into<Reply>,
+    {
+        let handler: Handler = Arc::new(move |msg| {
+            let fut = handler(msg);
+            Box::pin(async move { fut.await.map(Into::into) })
+        });
+        self.routes.push(Route {
+            pattern: pattern.to_string(),
+            handler,
+        });
+        self
+    }
+
+    /// Maximum number of handlers running at once. Default 16.
+    pub fn max_concurrency(mut self, max_concurrency: NonZeroUsize) -> Self {
+        self.max_concurrency = max_concurrency.get();
+        self
+    }
+
+    /// Requests waiting for a free handler slot. Requests arriving while
+    /// the queue is full are dropped with a warning, their requesters time
+    /// out. Default 1024.
+    pub fn queue_capacity(mut self, queue_capacity: NonZeroUsize) -> Self {
+        self.queue_capacity = queue_capacity.get();
+        self
+    }
+
+    /// Subscribe every pattern and serve requests until the future is
+    /// dropped, which removes the subscriptions. Must run within a tokio
+    /// runtime.
+    pub async fn run(self) -> Result<(), ResponderError> {
+        ensure!(!self.routes.is_empty(), NoHandlersSnafu);
+        let (sender, receiver) = bounded(self.queue_capacity);
+        let mut subscriptions: Vec<TopicDispatch> = Vec::with_capacity(self.routes.len());
+        for (index, route) in self.routes.iter().enumerate() {
+            let sender = sender.clone();
+            let pattern = route.pattern.clone();
+            let dispatch = self
+                .client
+                .subscribe_with_handler(&route.pattern, move |msg| {
+                    // Runs on the context thread: never wait for room.
+                    match sender.try_send((index, msg)) {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            tracing::warn!("responder queue full, dropped request on {}", pattern)
+                        }
+                        Err(e) => tracing::error!("responder queue closed: {}", e),
+                    }
+                })
+                .context(SubscribeSnafu {
+                    pattern: route.pattern.clone(),
+                })?;
+            subscriptions.push(dispatch);
+        }
+        self.serve(&receiver).await;
+        // `subscriptions` is dropped here or when the future is cancelled,
+        // removing them.
+        Ok(())
+    }
+
+    async fn serve(&self, receiver: &Receiver<(usize, SolMsg)>) {
+        let slots = Arc::new(Semaphore::new(self.max_concurrency));
+        while let Ok((index, request)) = receiver.as_async().recv().await {
+            if request.is_reply() || request.get_reply_to().is_err() {
+                tracing::debug!("responder ignored msg without reply-to");
+                continue;
+            }
+            // The semaphore is never closed.
+            let slot = slots.clone().acquire_owned().await.unwrap();
+            let handler = self.routes[index].handler.clone();
+            let client = self.client.clone();
+            // The handler gets a copy, the original is needed to reply.
+            let handled = request
+                .dup()
+                .map_err(|e| HandlerError::new(INTERNAL_ERROR, e.to_string()));
+            tokio::spawn(async move {
+                let outcome = match handled {
+                    // A panicking handler only takes down its own task.
+                    Ok(handled) => tokio::spawn(handler(handled)).await.unwrap_or_else(|_| {
+                        Err(HandlerError::new(INTERNAL_ERROR, "handler panicked"))
+                    }),
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = reply(&client, &request, outcome) {
+                    tracing::error!("responder reply error: {}", e);
+                }
+                drop(slot);
+            });
+        }
+    }
+}
+
+/// Reply message for `request`: `outcome`'s payload and user properties,
+/// or its error envelope, with the request's correlation id.
+pub(crate) fn build_reply(
+    request: &SolMsg,
+    outcome: Result<Reply, HandlerError>,
+) -> Result<SolMsg, SolMsgError> {
+    let mut reply_msg = SolMsg::new()?;
+    if let Ok(corrid) = request.get_correlation_id() {
+        reply_msg.set_correlation_id(&corrid);
+    }
+    match outcome {
+        Ok(reply) => {
+            for (key, value) in &reply.user_props {
+                reply_msg.set_user_prop(key, value, USER_PROP_MAP_SIZE);
+            }
+            reply_msg.set_binary_attachment(&reply.payload);
+        }
+        Err(error) => {
+            reply_msg.set_user_prop(ERROR_CODE_PROP, &error.code, USER_PROP_MAP_SIZE);
+            reply_msg.set_binary_attachment(error.message.as_bytes());
+        }
+    }
+    Ok(reply_msg)
+}
+
+#[derive(Debug, Snafu)]
+enum ReplyError {
+    #[snafu(display("build reply {}", source))]
+    Build { source: SolMsgError },
+    #[snafu(display("send reply code: {code:?}"))]
+    Send { code: SolClientReturnCode },
+}
+
+fn reply(
+    client: &SolClient,
+    request: &SolMsg,
+    outcome: Result<Reply, HandlerError>,
+) -> Result<(), ReplyError> {
+    let reply_msg = build_reply(request, outcome).context(BuildSnafu)?;
+    let code = client.send_reply(request, &reply_msg);
+    ensure!(code == SolClientReturnCode::Ok, SendSnafu { code });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SolClientLogLevel;
+
+    #[test]
+    fn test_build_reply_propagates_correlation_id() {
+        let mut request = SolMsg::new().unwrap();
+        request.set_correlation_id("req-1");
+
+        let ok = build_reply(&request, Ok(Reply::new("done").with_user_prop("k", "v"))).unwrap();
+        assert_eq!(ok.get_correlation_id().unwrap(), "req-1");
+        assert_eq!(ok.get_binary_attachment().unwrap().as_ref(), b"done");
+        assert_eq!(HandlerError::from_reply(&ok), None);
+
+        let error = HandlerError::new("not_found", "no such order");
+        let err = build_reply(&request, Err(error.clone())).unwrap();
+        assert_eq!(err.get_correlation_id().unwrap(), "req-1");
+        assert_eq!(HandlerError::from_reply(&err), Some(error));
+    }
+
+    #[tokio::test]
+    async fn test_run_without_handlers_or_session() {
+        let client = Arc::new(SolClient::new(SolClientLogLevel::Notice).unwrap());
+        assert_eq!(
+            Responder::new(client.clone()).run().await,
+            Err(ResponderError::NoHandlers)
+        );
+        let res = Responder::new(client)
+            .handle("api/>", |_req| async { Ok::<_, HandlerError>("pong") })
+            .run()
+            .await;
+        assert!(matches!(res, Err(ResponderError::Subscribe { .. })));
+    }
+}
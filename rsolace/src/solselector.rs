@@ -0,0 +1,866 @@
+//! SQL-92 message selectors.
+//!
+//! A flow bound with [`crate::solflow::FlowProps::selector`] only receives
+//! the messages whose user properties match the selector; the broker does
+//! the filtering. [`Selector`] parses and evaluates the same syntax locally,
+//! so a selector can be validated before binding and unit tested against
+//! messages without a broker.
+//!
+//! Supported: `AND`, `OR`, `NOT`, `=`, `<>`, `<`, `<=`, `>`, `>=`, `+`, `-`,
+//! `*`, `/`, `[NOT] BETWEEN`, `[NOT] IN`, `[NOT] LIKE ... [ESCAPE ...]` and
+//! `IS [NOT] NULL`, with string, integer, float and boolean literals.
+//! Keywords are case-insensitive, property names are case-sensitive.
+//! A missing property is NULL and comparisons with NULL are unknown, which
+//! never matches.
+
+use super::solmsg::SolMsg;
+use snafu::prelude::Snafu;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Snafu, PartialEq)]
+pub enum SelectorError {
+    #[snafu(display("Selector parse error at {position}: {message}"))]
+    Parse { position: usize, message: String },
+}
+
+fn parse_error(position: usize, message: impl Into<String>) -> SelectorError {
+    SelectorError::Parse {
+        position,
+        message: message.into(),
+    }
+}
+
+/// Value of a property or literal in a selector expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectorValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl SelectorValue {
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            SelectorValue::Int(v) => Some(*v as f64),
+            SelectorValue::Float(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for SelectorValue {
+    fn from(v: bool) -> Self {
+        SelectorValue::Bool(v)
+    }
+}
+
+impl From<i64> for SelectorValue {
+    fn from(v: i64) -> Self {
+        SelectorValue::Int(v)
+    }
+}
+
+impl From<i32> for SelectorValue {
+    fn from(v: i32) -> Self {
+        SelectorValue::Int(v as i64)
+    }
+}
+
+impl From<f64> for SelectorValue {
+    fn from(v: f64) -> Self {
+        SelectorValue::Float(v)
+    }
+}
+
+impl From<&str> for SelectorValue {
+    fn from(v: &str) -> Self {
+        SelectorValue::String(v.to_string())
+    }
+}
+
+impl From<String> for SelectorValue {
+    fn from(v: String) -> Self {
+        SelectorValue::String(v)
+    }
+}
+
+/// A parsed message selector.
+///
+/// ```ignore
+/// let selector = Selector::parse("region = 'eu' AND priority > 3")?;
+/// let flow = client.create_flow(
+///     FlowProps::default()
+///         .queue("orders/in")
+///         .selector(Some(selector.as_str())),
+/// )?;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    source: String,
+    expr: Expr,
+}
+
+impl Selector {
+    pub fn parse(selector: &str) -> Result<Selector, SelectorError> {
+        let mut parser = Parser {
+            tokens: tokenize(selector)?,
+            next: 0,
+            end: selector.len(),
+        };
+        let expr = parser.boolean_expr()?;
+        if parser.next < parser.tokens.len() {
+            return Err(parse_error(parser.position(), "unexpected token"));
+        }
+        Ok(Selector {
+            source: selector.to_string(),
+            expr,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Evaluate the selector, resolving property names with `lookup`.
+    /// `None` from `lookup` is a missing (NULL) property.
+    pub fn evaluate<F>(&self, lookup: F) -> bool
+    where
+        F: Fn(&str) -> Option<SelectorValue>,
+    {
+        self.expr.eval(&lookup) == Some(SelectorValue::Bool(true))
+    }
+
+    pub fn matches_props(&self, props: &HashMap<String, SelectorValue>) -> bool {
+        self.evaluate(|name| props.get(name).cloned())
+    }
+
+    /// Evaluate the selector against the user properties of `msg`.
+    ///
+    /// `SolMsg::set_user_prop` stores strings, so a property set that way
+    /// only matches string comparisons (`region = 'eu'`), as on the broker.
+    pub fn matches(&self, msg: &SolMsg) -> bool {
+        self.evaluate(|name| msg.get_user_prop(name).ok().map(SelectorValue::String))
+    }
+}
+
+impl FromStr for Selector {
+    type Err = SelectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Selector::parse(s)
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum LikePart {
+    /// `%`
+    AnySeq,
+    /// `_`
+    AnyChar,
+    Char(char),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Literal(SelectorValue),
+    Property(String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(CompareOp, Box<Expr>, Box<Expr>),
+    Arith(ArithOp, Box<Expr>, Box<Expr>),
+    Neg(Box<Expr>),
+    Between {
+        expr: Box<Expr>,
+        low: Box<Expr>,
+        high: Box<Expr>,
+        negated: bool,
+    },
+    In {
+        expr: Box<Expr>,
+        list: Vec<String>,
+        negated: bool,
+    },
+    Like {
+        expr: Box<Expr>,
+        pattern: Vec<LikePart>,
+        negated: bool,
+    },
+    IsNull {
+        expr: Box<Expr>,
+        negated: bool,
+    },
+}
+
+impl Expr {
+    /// Whether the expression can produce a boolean, checked when it is
+    /// used as a condition.
+    fn is_boolean(&self) -> bool {
+        !matches!(
+            self,
+            Expr::Arith(..)
+                | Expr::Neg(_)
+                | Expr::Literal(SelectorValue::Int(_))
+                | Expr::Literal(SelectorValue::Float(_))
+                | Expr::Literal(SelectorValue::String(_))
+        )
+    }
+
+    /// `None` is SQL NULL / unknown.
+    fn eval<F>(&self, lookup: &F) -> Option<SelectorValue>
+    where
+        F: Fn(&str) -> Option<SelectorValue>,
+    {
+        match self {
+            Expr::Literal(v) => Some(v.clone()),
+            Expr::Property(name) => lookup(name),
+            Expr::Not(e) => e.eval_bool(lookup).map(|b| SelectorValue::Bool(!b)),
+            Expr::And(l, r) => {
+                let l = l.eval_bool(lookup);
+                if l == Some(false) {
+                    return Some(SelectorValue::Bool(false));
+                }
+                match (l, r.eval_bool(lookup)) {
+                    (_, Some(false)) => Some(SelectorValue::Bool(false)),
+                    (Some(true), Some(true)) => Some(SelectorValue::Bool(true)),
+                    _ => None,
+                }
+            }
+            Expr::Or(l, r) => {
+                let l = l.eval_bool(lookup);
+                if l == Some(true) {
+                    return Some(SelectorValue::Bool(true));
+                }
+                match (l, r.eval_bool(lookup)) {
+                    (_, Some(true)) => Some(SelectorValue::Bool(true)),
+                    (Some(false), Some(false)) => Some(SelectorValue::Bool(false)),
+                    _ => None,
+                }
+            }
+            Expr::Compare(op, l, r) => {
+                compare(*op, &l.eval(lookup)?, &r.eval(lookup)?).map(SelectorValue::Bool)
+            }
+            Expr::Arith(op, l, r) => arith(*op, &l.eval(lookup)?, &r.eval(lookup)?),
+            Expr::Neg(e) => match e.eval(lookup)? {
+                SelectorValue::Int(v) => v.checked_neg().map(SelectorValue::Int),
+                SelectorValue::Float(v) => Some(SelectorValue::Float(-v)),
+                _ => None,
+            },
+            Expr::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => {
+                let v = expr.eval(lookup)?;
+                let within = compare(CompareOp::Ge, &v, &low.eval(lookup)?)?
+                    && compare(CompareOp::Le, &v, &high.eval(lookup)?)?;
+                Some(SelectorValue::Bool(within != *negated))
+            }
+            Expr::In {
+                expr,
+                list,
+                negated,
+            } => match expr.eval(lookup)? {
+                SelectorValue::String(v) => {
+                    Some(SelectorValue::Bool(list.contains(&v) != *negated))
+                }
+                _ => None,
+            },
+            Expr::Like {
+                expr,
+                pattern,
+                negated,
+            } => match expr.eval(lookup)? {
+                SelectorValue::String(v) => {
+                    let text: Vec<char> = v.chars().collect();
+                    Some(SelectorValue::Bool(like_match(pattern, &text) != *negated))
+                }
+                _ => None,
+            },
+            Expr::IsNull { expr, negated } => {
+                Some(SelectorValue::Bool(expr.eval(lookup).is_none() != *negated))
+            }
+        }
+    }
+
+    fn eval_bool<F>(&self, lookup: &F) -> Option<bool>
+    where
+        F: Fn(&str) -> Option<SelectorValue>,
+    {
+        match self.eval(lookup)? {
+            SelectorValue::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+/// Numbers compare with each other, strings and booleans only support
+/// `=` and `<>` against their own type. Anything else is unknown.
+fn compare(op: CompareOp, l: &SelectorValue, r: &SelectorValue) -> Option<bool> {
+    let equality_only = matches!(op, CompareOp::Eq | CompareOp::Ne);
+    let ord = match (l, r) {
+        (SelectorValue::Int(a), SelectorValue::Int(b)) => a.cmp(b),
+        (SelectorValue::String(a), SelectorValue::String(b)) if equality_only => a.cmp(b),
+        (SelectorValue::Bool(a), SelectorValue::Bool(b)) if equality_only => a.cmp(b),
+        _ => l.as_f64()?.partial_cmp(&r.as_f64()?)?,
+    };
+    Some(match op {
+        CompareOp::Eq => ord == Ordering::Equal,
+        CompareOp::Ne => ord != Ordering::Equal,
+        CompareOp::Lt => ord == Ordering::Less,
+        CompareOp::Le => ord != Ordering::Greater,
+        CompareOp::Gt => ord == Ordering::Greater,
+        CompareOp::Ge => ord != Ordering::Less,
+    })
+}
+
+fn arith(op: ArithOp, l: &SelectorValue, r: &SelectorValue) -> Option<SelectorValue> {
+    if let (SelectorValue::Int(a), SelectorValue::Int(b)) = (l, r) {
+        return match op {
+            ArithOp::Add => a.checked_add(*b),
+            ArithOp::Sub => a.checked_sub(*b),
+            ArithOp::Mul => a.checked_mul(*b),
+            ArithOp::Div => a.checked_div(*b),
+        }
+        .map(SelectorValue::Int);
+    }
+    let (a, b) = (l.as_f64()?, r.as_f64()?);
+    Some(SelectorValue::Float(match op {
+        ArithOp::Add => a + b,
+        ArithOp::Sub => a - b,
+        ArithOp::Mul => a * b,
+        ArithOp::Div => a / b,
+    }))
+}
+
+fn like_match(pattern: &[LikePart], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Last `%` seen and the text position it currently stands for.
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(LikePart::AnySeq) => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(LikePart::AnyChar) => {
+                p += 1;
+                t += 1;
+            }
+            Some(LikePart::Char(c)) if *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((bp, bt)) => {
+                    backtrack = Some((bp, bt + 1));
+                    p = bp + 1;
+                    t = bt + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|part| *part == LikePart::AnySeq)
+}
+
+fn like_pattern(
+    pattern: &str,
+    escape: Option<char>,
+    position: usize,
+) -> Result<Vec<LikePart>, SelectorError> {
+    let mut parts = Vec::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if Some(c) == escape {
+            match chars.next() {
+                Some(c) => parts.push(LikePart::Char(c)),
+                None => {
+                    return Err(parse_error(
+                        position,
+                        "LIKE pattern ends with the escape character",
+                    ))
+                }
+            }
+        } else if c == '%' {
+            parts.push(LikePart::AnySeq);
+        } else if c == '_' {
+            parts.push(LikePart::AnyChar);
+        } else {
+            parts.push(LikePart::Char(c));
+        }
+    }
+    Ok(parts)
+}
+
+const KEYWORDS: [&str; 11] = [
+    "AND", "OR", "NOT", "BETWEEN", "IN", "LIKE", "ESCAPE", "IS", "NULL", "TRUE", "FALSE",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Identifier(String),
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Keyword(&'static str),
+    Symbol(&'static str),
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, SelectorError> {
+    let chars: Vec<(usize, char)> = input.char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (position, c) = chars[i];
+        let next = chars.get(i + 1).map(|(_, c)| *c);
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '\'' {
+            // '' inside a string literal is an escaped quote
+            let mut s = String::new();
+            i += 1;
+            loop {
+                match chars.get(i).map(|(_, c)| *c) {
+                    None => return Err(parse_error(position, "unterminated string literal")),
+                    Some('\'') if chars.get(i + 1).map(|(_, c)| *c) == Some('\'') => {
+                        s.push('\'');
+                        i += 2;
+                    }
+                    Some('\'') => {
+                        i += 1;
+                        break;
+                    }
+                    Some(c) => {
+                        s.push(c);
+                        i += 1;
+                    }
+                }
+            }
+            tokens.push((position, Token::Str(s)));
+        } else if c.is_ascii_digit() || (c == '.' && next.is_some_and(|n| n.is_ascii_digit())) {
+            let start = i;
+            while let Some((_, c)) = chars.get(i) {
+                let after_exponent = i > start && matches!(chars[i - 1].1, 'e' | 'E');
+                if c.is_ascii_alphanumeric()
+                    || *c == '.'
+                    || (after_exponent && matches!(c, '+' | '-'))
+                {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            let text: String = chars[start..i].iter().map(|(_, c)| *c).collect();
+            let token = if text.contains(['.', 'e', 'E']) {
+                text.parse().ok().map(Token::Float)
+            } else {
+                text.parse().ok().map(Token::Int)
+            };
+            match token {
+                Some(token) => tokens.push((position, token)),
+                None => return Err(parse_error(position, format!("invalid number {text}"))),
+            }
+        } else if c.is_alphabetic() || c == '_' || c == '$' {
+            let start = i;
+            while let Some((_, c)) = chars.get(i) {
+                if c.is_alphanumeric() || *c == '_' || *c == '$' {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            let text: String = chars[start..i].iter().map(|(_, c)| *c).collect();
+            let upper = text.to_ascii_uppercase();
+            match KEYWORDS.iter().find(|kw| **kw == upper) {
+                Some(kw) => tokens.push((position, Token::Keyword(kw))),
+                None => tokens.push((position, Token::Identifier(text))),
+            }
+        } else {
+            let symbol = match (c, next) {
+                ('<', Some('>')) => "<>",
+                ('<', Some('=')) => "<=",
+                ('>', Some('=')) => ">=",
+                ('=', _) => "=",
+                ('<', _) => "<",
+                ('>', _) => ">",
+                ('+', _) => "+",
+                ('-', _) => "-",
+                ('*', _) => "*",
+                ('/', _) => "/",
+                ('(', _) => "(",
+                (')', _) => ")",
+                (',', _) => ",",
+                _ => return Err(parse_error(position, format!("unexpected character {c:?}"))),
+            };
+            i += symbol.len();
+            tokens.push((position, Token::Symbol(symbol)));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser, one method per precedence level.
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.next)
+            .map_or(self.end, |(position, _)| *position)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(_, token)| token)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.next += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, kw: &'static str) -> bool {
+        self.eat(&Token::Keyword(kw))
+    }
+
+    fn eat_symbol(&mut self, symbol: &'static str) -> bool {
+        self.eat(&Token::Symbol(symbol))
+    }
+
+    fn expect(&mut self, token: Token, what: &str) -> Result<(), SelectorError> {
+        if self.eat(&token) {
+            Ok(())
+        } else {
+            Err(parse_error(self.position(), format!("expected {what}")))
+        }
+    }
+
+    fn string_literal(&mut self) -> Result<String, SelectorError> {
+        match self.peek() {
+            Some(Token::Str(s)) => {
+                let s = s.clone();
+                self.next += 1;
+                Ok(s)
+            }
+            _ => Err(parse_error(self.position(), "expected a string literal")),
+        }
+    }
+
+    /// Parse with `parse` and check the result can be used as a condition.
+    fn condition(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Expr, SelectorError>,
+    ) -> Result<Box<Expr>, SelectorError> {
+        let position = self.position();
+        let expr = parse(self)?;
+        if !expr.is_boolean() {
+            return Err(parse_error(position, "expected a boolean expression"));
+        }
+        Ok(Box::new(expr))
+    }
+
+    fn boolean_expr(&mut self) -> Result<Expr, SelectorError> {
+        Ok(*self.condition(Self::or_expr)?)
+    }
+
+    fn or_expr(&mut self) -> Result<Expr, SelectorError> {
+        let mut expr = self.and_expr()?;
+        while self.eat_keyword("OR") {
+            let left = Box::new(expr);
+            if !left.is_boolean() {
+                return Err(parse_error(self.position(), "OR needs boolean operands"));
+            }
+            expr = Expr::Or(left, self.condition(Self::and_expr)?);
+        }
+        Ok(expr)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr, SelectorError> {
+        let mut expr = self.not_expr()?;
+        while self.eat_keyword("AND") {
+            let left = Box::new(expr);
+            if !left.is_boolean() {
+                return Err(parse_error(self.position(), "AND needs boolean operands"));
+            }
+            expr = Expr::And(left, self.condition(Self::not_expr)?);
+        }
+        Ok(expr)
+    }
+
+    fn not_expr(&mut self) -> Result<Expr, SelectorError> {
+        if self.eat_keyword("NOT") {
+            return Ok(Expr::Not(self.condition(Self::not_expr)?));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, SelectorError> {
+        let left = self.additive()?;
+        let op = match self.peek() {
+            Some(Token::Symbol("=")) => Some(CompareOp::Eq),
+            Some(Token::Symbol("<>")) => Some(CompareOp::Ne),
+            Some(Token::Symbol("<")) => Some(CompareOp::Lt),
+            Some(Token::Symbol("<=")) => Some(CompareOp::Le),
+            Some(Token::Symbol(">")) => Some(CompareOp::Gt),
+            Some(Token::Symbol(">=")) => Some(CompareOp::Ge),
+            _ => None,
+        };
+        if let Some(op) = op {
+            self.next += 1;
+            let right = self.additive()?;
+            return Ok(Expr::Compare(op, Box::new(left), Box::new(right)));
+        }
+        if self.eat_keyword("IS") {
+            let negated = self.eat_keyword("NOT");
+            self.expect(Token::Keyword("NULL"), "NULL")?;
+            return Ok(Expr::IsNull {
+                expr: Box::new(left),
+                negated,
+            });
+        }
+
+        let negated = self.eat_keyword("NOT");
+        let expr = Box::new(left);
+        if self.eat_keyword("BETWEEN") {
+            let low = Box::new(self.additive()?);
+            self.expect(Token::Keyword("AND"), "AND")?;
+            let high = Box::new(self.additive()?);
+            Ok(Expr::Between {
+                expr,
+                low,
+                high,
+                negated,
+            })
+        } else if self.eat_keyword("IN") {
+            self.expect(Token::Symbol("("), "'('")?;
+            let mut list = vec![self.string_literal()?];
+            while self.eat_symbol(",") {
+                list.push(self.string_literal()?);
+            }
+            self.expect(Token::Symbol(")"), "')'")?;
+            Ok(Expr::In {
+                expr,
+                list,
+                negated,
+            })
+        } else if self.eat_keyword("LIKE") {
+            let position = self.position();
+            let pattern = self.string_literal()?;
+            let escape = if self.eat_keyword("ESCAPE") {
+                let escape_position = self.position();
+                let escape = self.string_literal()?;
+                let mut chars = escape.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Some(c),
+                    _ => {
+                        return Err(parse_error(
+                            escape_position,
+                            "ESCAPE must be a single character",
+                        ))
+                    }
+                }
+            } else {
+                None
+            };
+            Ok(Expr::Like {
+                expr,
+                pattern: like_pattern(&pattern, escape, position)?,
+                negated,
+            })
+        } else if negated {
+            Err(parse_error(
+                self.position(),
+                "expected BETWEEN, IN or LIKE after NOT",
+            ))
+        } else {
+            Ok(*expr)
+        }
+    }
+
+    fn additive(&mut self) -> Result<Expr, SelectorError> {
+        let mut expr = self.multiplicative()?;
+        loop {
+            let op = if self.eat_symbol("+") {
+                ArithOp::Add
+            } else if self.eat_symbol("-") {
+                ArithOp::Sub
+            } else {
+                return Ok(expr);
+            };
+            let right = self.multiplicative()?;
+            expr = Expr::Arith(op, Box::new(expr), Box::new(right));
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, SelectorError> {
+        let mut expr = self.unary()?;
+        loop {
+            let op = if self.eat_symbol("*") {
+                ArithOp::Mul
+            } else if self.eat_symbol("/") {
+                ArithOp::Div
+            } else {
+                return Ok(expr);
+            };
+            let right = self.unary()?;
+            expr = Expr::Arith(op, Box::new(expr), Box::new(right));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, SelectorError> {
+        if self.eat_symbol("-") {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        if self.eat_symbol("+") {
+            return self.unary();
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, SelectorError> {
+        let position = self.position();
+        let expr = match self.peek() {
+            Some(Token::Str(s)) => Expr::Literal(SelectorValue::String(s.clone())),
+            Some(Token::Int(v)) => Expr::Literal(SelectorValue::Int(*v)),
+            Some(Token::Float(v)) => Expr::Literal(SelectorValue::Float(*v)),
+            Some(Token::Keyword("TRUE")) => Expr::Literal(SelectorValue::Bool(true)),
+            Some(Token::Keyword("FALSE")) => Expr::Literal(SelectorValue::Bool(false)),
+            Some(Token::Identifier(name)) => Expr::Property(name.clone()),
+            Some(Token::Symbol("(")) => {
+                self.next += 1;
+                let expr = self.or_expr()?;
+                self.expect(Token::Symbol(")"), "')'")?;
+                return Ok(expr);
+            }
+            _ => {
+                return Err(parse_error(
+                    position,
+                    "expected a literal, property name or '('",
+                ))
+            }
+        };
+        self.next += 1;
+        Ok(expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn props(pairs: &[(&str, SelectorValue)]) -> HashMap<String, SelectorValue> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_selector_region() {
+        let selector = Selector::parse("region = 'eu' OR region IN ('uk', 'ch')").unwrap();
+        assert!(selector.matches_props(&props(&[("region", "eu".into())])));
+        assert!(selector.matches_props(&props(&[("region", "ch".into())])));
+        assert!(!selector.matches_props(&props(&[("region", "us".into())])));
+        // missing property is NULL, which never matches
+        assert!(!selector.matches_props(&props(&[])));
+    }
+
+    #[test]
+    fn test_selector_numeric() {
+        let selector = Selector::parse("qty * 2 >= 10 AND price NOT BETWEEN 1.5 AND 3").unwrap();
+        assert!(selector.matches_props(&props(&[("qty", 5.into()), ("price", 4.0.into())])));
+        assert!(!selector.matches_props(&props(&[("qty", 4.into()), ("price", 4.0.into())])));
+        assert!(!selector.matches_props(&props(&[("qty", 5.into()), ("price", 2.into())])));
+        // strings are not numbers
+        assert!(!selector.matches_props(&props(&[("qty", "5".into()), ("price", 4.0.into())])));
+    }
+
+    #[test]
+    fn test_selector_null_and_unknown() {
+        let selector = Selector::parse("NOT (region = 'eu')").unwrap();
+        assert!(!selector.matches_props(&props(&[])));
+        let selector = Selector::parse("region IS NULL OR region <> 'eu'").unwrap();
+        assert!(selector.matches_props(&props(&[])));
+        assert!(!selector.matches_props(&props(&[("region", "eu".into())])));
+        // unknown OR true is true
+        let selector = Selector::parse("region = 'eu' OR TRUE").unwrap();
+        assert!(selector.matches_props(&props(&[])));
+    }
+
+    #[test]
+    fn test_selector_like() {
+        let selector = Selector::parse("sku like 'A\\_%-__' escape '\\'").unwrap();
+        assert!(selector.matches_props(&props(&[("sku", "A_123-xy".into())])));
+        assert!(!selector.matches_props(&props(&[("sku", "AB123-xy".into())])));
+        assert!(!selector.matches_props(&props(&[("sku", "A_123-x".into())])));
+        let selector = Selector::parse("name NOT LIKE '%''s%'").unwrap();
+        assert!(selector.matches_props(&props(&[("name", "orders".into())])));
+        assert!(!selector.matches_props(&props(&[("name", "bob's".into())])));
+    }
+
+    #[test]
+    fn test_selector_parse_errors() {
+        assert_eq!(
+            Selector::parse("region = 'eu"),
+            Err(parse_error(9, "unterminated string literal"))
+        );
+        assert_eq!(
+            Selector::parse("region = 'eu' AND"),
+            Err(parse_error(17, "expected a literal, property name or '('"))
+        );
+        assert_eq!(
+            Selector::parse("qty + 1"),
+            Err(parse_error(0, "expected a boolean expression"))
+        );
+        assert!(Selector::parse("region NOT 'eu'").is_err());
+        assert!(Selector::parse("region = 'eu')").is_err());
+        assert!(Selector::parse("sku LIKE 'a' ESCAPE 'ab'").is_err());
+    }
+
+    #[test]
+    fn test_selector_matches_msg() {
+        let mut msg = SolMsg::new().unwrap();
+        msg.set_user_prop("region", "eu", 10);
+        let selector: Selector = "region = 'eu'".parse().unwrap();
+        assert!(selector.matches(&msg));
+        assert!(!Selector::parse("region = 'us'").unwrap().matches(&msg));
+        assert_eq!(selector.to_string(), "region = 'eu'");
+    }
+}
@@ -0,0 +1,269 @@
+//! Registry of the topic subscriptions a `SolClient` made.
+//!
+//! The C API forgets a session's subscriptions when the session is
+//! destroyed, which `SolClient::connect` does before every new session.
+//! The registry remembers every topic subscribed through
+//! `SolClient::subscribe` / `subscribe_ext` until it is unsubscribed, so
+//! the client can subscribe them again on the next session, and tracks
+//! whether the broker confirmed each of them.
+//!
+//! `SolClient::subscribe_confirmed` waits for that confirmation and fails
+//! with a [`SubscriptionError`] carrying the broker's sub code.
+
+use super::solevent::SolEvent;
+use super::types::{
+    ErrorInfo, SolClientReturnCode, SolClientSessionEvent, SolClientSubCode, SolClientSubCodeOrRaw,
+    SolClientSubscribeFlags,
+};
+use snafu::prelude::Snafu;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(Debug, Snafu, PartialEq)]
+pub enum SubscriptionError {
+    #[snafu(display("Subscription {topic} on a session that is not connected"))]
+    SessionNotConnected { topic: String },
+    #[snafu(display("Subscription {topic} code: {code:?}, Error {error:?}"))]
+    Subscribe {
+        topic: String,
+        code: SolClientReturnCode,
+        error: ErrorInfo,
+    },
+    #[snafu(display("Subscription {topic} refused by broker, Error {error:?}"))]
+    Refused { topic: String, error: ErrorInfo },
+    #[snafu(display("Subscription {topic} outcome lost, session destroyed before confirm"))]
+    Unconfirmed { topic: String },
+    #[snafu(display("Subscription {topic} not confirmed within {timeout:?}"))]
+    ConfirmTimeout { topic: String, timeout: Duration },
+}
+
+impl SubscriptionError {
+    /// Sub code of the failure, e.g. `SubscriptionAclDenied` or
+    /// `InvalidTopicSyntax`. `None` when the API reported none.
+    pub fn sub_code(&self) -> Option<SolClientSubCode> {
+        match self {
+            SubscriptionError::Subscribe { error, .. }
+            | SubscriptionError::Refused { error, .. } => match error.sub_code {
+                SolClientSubCodeOrRaw::SubCode(sub_code) => Some(sub_code),
+                SolClientSubCodeOrRaw::Raw(_) => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// The topic among `topics` a `SubscriptionOk` / `SubscriptionError` event
+/// info is about. The info of these events is the subscription's topic, so
+/// only an exact match counts: events of subscriptions made elsewhere,
+/// e.g. `orders/eu` for a registered `orders`, match nothing.
+pub(crate) fn match_event_topic<'a>(
+    info: &str,
+    topics: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let info = info.trim();
+    topics.into_iter().find(|topic| *topic == info)
+}
+
+/// Broker outcome of a subscription.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubscriptionStatus {
+    /// Sent, no confirmation yet.
+    Pending,
+    /// The broker added the subscription.
+    Confirmed,
+    /// The broker refused the subscription.
+    Failed(ErrorInfo),
+}
+
+/// One registered topic subscription.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subscription {
+    pub topic: String,
+    pub flag: SolClientSubscribeFlags,
+    pub status: SubscriptionStatus,
+}
+
+/// Topic subscriptions in the order they were first made.
+#[derive(Debug, Default)]
+pub struct SubscriptionRegistry {
+    entries: Mutex<Vec<Subscription>>,
+}
+
+impl SubscriptionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `topic`, replacing the flag and status of an earlier
+    /// subscription to the same topic.
+    pub fn insert(&self, topic: &str, flag: SolClientSubscribeFlags, status: SubscriptionStatus) {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.iter_mut().find(|entry| entry.topic == topic) {
+            Some(entry) => {
+                entry.flag = flag;
+                entry.status = status;
+            }
+            None => entries.push(Subscription {
+                topic: topic.to_string(),
+                flag,
+                status,
+            }),
+        }
+    }
+
+    /// Forget `topic`. Returns whether it was registered.
+    pub fn remove(&self, topic: &str) -> bool {
+        let mut entries = self.entries.lock().unwrap();
+        let len = entries.len();
+        entries.retain(|entry| entry.topic != topic);
+        entries.len() != len
+    }
+
+    pub fn set_status(&self, topic: &str, status: SubscriptionStatus) {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.iter_mut().find(|entry| entry.topic == topic) {
+            entry.status = status;
+        }
+    }
+
+    pub fn get(&self, topic: &str) -> Option<Subscription> {
+        let entries = self.entries.lock().unwrap();
+        entries.iter().find(|entry| entry.topic == topic).cloned()
+    }
+
+    pub fn snapshot(&self) -> Vec<Subscription> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().unwrap().is_empty()
+    }
+
+    /// The registered topic a `SubscriptionOk` / `SubscriptionError` event
+    /// is about.
+    pub fn event_topic(&self, event: &SolEvent) -> Option<String> {
+        let entries = self.entries.lock().unwrap();
+        match_event_topic(
+            &event.info,
+            entries.iter().map(|entry| entry.topic.as_str()),
+        )
+        .map(str::to_string)
+    }
+
+    /// Update the status of the subscription `event` confirms or refuses.
+    /// `error` is the error info of a `SubscriptionError`. Returns the
+    /// topic of the updated subscription.
+    pub fn on_event(&self, event: &SolEvent, error: Option<ErrorInfo>) -> Option<String> {
+        let status = match event.session_event {
+            SolClientSessionEvent::SubscriptionOk => SubscriptionStatus::Confirmed,
+            SolClientSessionEvent::SubscriptionError => {
+                SubscriptionStatus::Failed(error.unwrap_or_else(|| ErrorInfo {
+                    sub_code: SolClientSubCodeOrRaw::Raw(event.response_code),
+                    error_str: event.info.clone(),
+                }))
+            }
+            _ => return None,
+        };
+        let topic = self.event_topic(event)?;
+        self.set_status(&topic, status);
+        Some(topic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_insert_remove_keeps_order() {
+        let registry = SubscriptionRegistry::new();
+        registry.insert(
+            "a/>",
+            SolClientSubscribeFlags::WaitForConfirm,
+            SubscriptionStatus::Confirmed,
+        );
+        registry.insert(
+            "b/*",
+            SolClientSubscribeFlags::RequestConfirm,
+            SubscriptionStatus::Pending,
+        );
+        registry.insert(
+            "a/>",
+            SolClientSubscribeFlags::RequestConfirm,
+            SubscriptionStatus::Pending,
+        );
+        let topics: Vec<_> = registry.snapshot().into_iter().map(|s| s.topic).collect();
+        assert_eq!(topics, vec!["a/>", "b/*"]);
+        assert_eq!(
+            registry.get("a/>").unwrap().flag,
+            SolClientSubscribeFlags::RequestConfirm
+        );
+        assert!(registry.remove("a/>"));
+        assert!(!registry.remove("a/>"));
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn test_registry_status_from_events() {
+        let registry = SubscriptionRegistry::new();
+        for topic in ["orders", "orders/eu"] {
+            registry.insert(
+                topic,
+                SolClientSubscribeFlags::RequestConfirm,
+                SubscriptionStatus::Pending,
+            );
+        }
+        let ok = SolEvent::new(SolClientSessionEvent::SubscriptionOk, 200, "orders");
+        assert_eq!(registry.on_event(&ok, None), Some("orders".to_string()));
+        assert_eq!(
+            registry.get("orders").unwrap().status,
+            SubscriptionStatus::Confirmed
+        );
+
+        let error = SolEvent::new(SolClientSessionEvent::SubscriptionError, 403, "orders/eu");
+        assert_eq!(
+            registry.on_event(&error, None),
+            Some("orders/eu".to_string())
+        );
+        assert!(matches!(
+            registry.get("orders/eu").unwrap().status,
+            SubscriptionStatus::Failed(_)
+        ));
+
+        let other = SolEvent::new(SolClientSessionEvent::SubscriptionOk, 200, "quotes");
+        assert_eq!(registry.on_event(&other, None), None);
+
+        // an unregistered subscription is not taken for a shorter topic
+        registry.remove("orders/eu");
+        registry.set_status("orders", SubscriptionStatus::Pending);
+        let unregistered = SolEvent::new(SolClientSessionEvent::SubscriptionOk, 200, "orders/eu");
+        assert_eq!(registry.on_event(&unregistered, None), None);
+        assert_eq!(
+            registry.get("orders").unwrap().status,
+            SubscriptionStatus::Pending
+        );
+    }
+
+    #[test]
+    fn test_subscription_error_sub_code() {
+        let error = SubscriptionError::Refused {
+            topic: "orders/eu".to_string(),
+            error: ErrorInfo {
+                sub_code: SolClientSubCodeOrRaw::SubCode(SolClientSubCode::SubscriptionAclDenied),
+                error_str: "Subscription ACL Denied".to_string(),
+            },
+        };
+        assert_eq!(
+            error.sub_code(),
+            Some(SolClientSubCode::SubscriptionAclDenied)
+        );
+        let lost = SubscriptionError::Unconfirmed {
+            topic: "orders/eu".to_string(),
+        };
+        assert_eq!(lost.sub_code(), None);
+    }
+}
@@ -0,0 +1,236 @@
+//! Temporary queues and topics.
+//!
+//! The broker names both after the session that created them, so only that
+//! session can consume from them: they make private inboxes, e.g. the
+//! reply-to destination of long-running requests. Each is owned by a handle
+//! that removes it from the broker when dropped.
+
+use super::solflow::{FlowProps, SolFlow, SolFlowError};
+use super::solmsg::{Destination, SolMsg};
+use super::types::{ErrorInfo, SolClientDestType, SolClientReturnCode, SolClientSubscribeFlags};
+use enum_primitive::FromPrimitive;
+use kanal::Receiver;
+use snafu::prelude::{ensure, Snafu};
+use snafu::ResultExt;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Snafu, PartialEq)]
+pub enum TemporaryError {
+    #[snafu(display("Temporary destination on a session that is not connected"))]
+    SessionNotConnected,
+    #[snafu(display("Temporary destination create name code: {code:?}, Error {error:?}"))]
+    CreateName {
+        code: SolClientReturnCode,
+        error: ErrorInfo,
+    },
+    #[snafu(display("Temporary topic subscribe {topic}, code: {code:?}, Error {error:?}"))]
+    Subscribe {
+        topic: String,
+        code: SolClientReturnCode,
+        error: ErrorInfo,
+    },
+    #[snafu(display("Temporary queue bind {name} {}", source))]
+    Bind { name: String, source: SolFlowError },
+}
+
+type CreateNameFn = unsafe extern "C" fn(
+    rsolace_sys::solClient_opaqueSession_pt,
+    *mut c_char,
+    usize,
+) -> rsolace_sys::solClient_returnCode_t;
+
+/// Ask the session for a unique temporary queue or topic name.
+fn create_name(
+    session_p: rsolace_sys::solClient_opaqueSession_pt,
+    session_alive: &AtomicBool,
+    create: CreateNameFn,
+    max_len: u32,
+) -> Result<String, TemporaryError> {
+    ensure!(
+        !session_p.is_null() && session_alive.load(Ordering::Acquire),
+        SessionNotConnectedSnafu
+    );
+    let mut buf = vec![0 as c_char; max_len as usize + 1];
+    let rt_code = unsafe { create(session_p, buf.as_mut_ptr(), buf.len()) };
+    let rt_code = SolClientReturnCode::from_i32(rt_code).unwrap();
+    ensure!(
+        rt_code == SolClientReturnCode::Ok,
+        CreateNameSnafu {
+            code: rt_code,
+            error: ErrorInfo::take_last().unwrap(),
+        }
+    );
+    let name = unsafe { CStr::from_ptr(buf.as_ptr()) };
+    Ok(name.to_string_lossy().into_owned())
+}
+
+/// A temporary queue, bound by its own flow.
+///
+/// Created with [`crate::solclient::SolClient::create_temporary_queue`].
+/// The queue exists as long as the flow is bound: dropping the handle
+/// unbinds the flow and the broker deletes the queue with the messages
+/// still spooled on it.
+///
+/// ```ignore
+/// let inbox = client.create_temporary_queue()?;
+/// let mut req = SolMsg::new()?;
+/// req.set_topic("orders/rebuild");
+/// req.set_reply_to(inbox.destination());
+/// client.send_msg(&req);
+/// let reply = inbox.get_msg_receiver().recv()?;
+/// ```
+pub struct TemporaryQueue {
+    destination: Destination,
+    flow: SolFlow,
+}
+
+impl TemporaryQueue {
+    pub(crate) fn new(
+        session_p: rsolace_sys::solClient_opaqueSession_pt,
+        session_alive: Arc<AtomicBool>,
+        props: FlowProps,
+    ) -> Result<TemporaryQueue, TemporaryError> {
+        let name = create_name(
+            session_p,
+            &session_alive,
+            rsolace_sys::solClient_session_createTemporaryQueueName,
+            rsolace_sys::SOLCLIENT_BUFINFO_MAX_QUEUENAME_SIZE,
+        )?;
+        let props = props.temporary_queue(&name);
+        let flow = SolFlow::new(session_p, session_alive, &props)
+            .context(BindSnafu { name: name.clone() })?;
+        Ok(TemporaryQueue {
+            destination: Destination::new(SolClientDestType::QueueTemp, &name),
+            flow,
+        })
+    }
+
+    /// Destination to publish to, or to set as a message's reply-to.
+    pub fn destination(&self) -> &Destination {
+        &self.destination
+    }
+
+    pub fn name(&self) -> &str {
+        &self.destination.dest
+    }
+
+    pub fn get_flow(&self) -> &SolFlow {
+        &self.flow
+    }
+
+    pub fn get_msg_receiver(&self) -> Receiver<SolMsg> {
+        self.flow.get_msg_receiver()
+    }
+
+    #[cfg(feature = "tokio")]
+    pub fn get_async_msg_receiver(&self) -> kanal::AsyncReceiver<SolMsg> {
+        self.flow.get_async_msg_receiver()
+    }
+}
+
+/// A temporary topic the session is subscribed to.
+///
+/// Created with [`crate::solclient::SolClient::create_temporary_topic`].
+/// Messages published to it arrive on the client's own msg receiver.
+/// Dropping the handle removes the subscription.
+pub struct TemporaryTopic {
+    destination: Destination,
+    session_p: rsolace_sys::solClient_opaqueSession_pt,
+    session_alive: Arc<AtomicBool>,
+}
+
+impl TemporaryTopic {
+    pub(crate) fn new(
+        session_p: rsolace_sys::solClient_opaqueSession_pt,
+        session_alive: Arc<AtomicBool>,
+    ) -> Result<TemporaryTopic, TemporaryError> {
+        let name = create_name(
+            session_p,
+            &session_alive,
+            rsolace_sys::solClient_session_createTemporaryTopicName,
+            rsolace_sys::SOLCLIENT_BUFINFO_MAX_TOPIC_SIZE,
+        )?;
+        let topic = CString::new(name.as_str()).unwrap();
+        let rt_code = unsafe {
+            rsolace_sys::solClient_session_topicSubscribeExt(
+                session_p,
+                SolClientSubscribeFlags::WaitForConfirm as rsolace_sys::solClient_subscribeFlags_t,
+                topic.as_ptr(),
+            )
+        };
+        let rt_code = SolClientReturnCode::from_i32(rt_code).unwrap();
+        ensure!(
+            rt_code == SolClientReturnCode::Ok,
+            SubscribeSnafu {
+                topic: name,
+                code: rt_code,
+                error: ErrorInfo::take_last().unwrap(),
+            }
+        );
+        Ok(TemporaryTopic {
+            destination: Destination::new(SolClientDestType::TopicTemp, &name),
+            session_p,
+            session_alive,
+        })
+    }
+
+    /// Destination to publish to, or to set as a message's reply-to.
+    pub fn destination(&self) -> &Destination {
+        &self.destination
+    }
+
+    pub fn name(&self) -> &str {
+        &self.destination.dest
+    }
+}
+
+impl Drop for TemporaryTopic {
+    fn drop(&mut self) {
+        // A destroyed session took its subscriptions with it.
+        if self.session_alive.load(Ordering::Acquire) {
+            let topic = CString::new(self.destination.dest.as_str()).unwrap();
+            let rt_code = unsafe {
+                rsolace_sys::solClient_session_topicUnsubscribeExt(
+                    self.session_p,
+                    0 as rsolace_sys::solClient_subscribeFlags_t,
+                    topic.as_ptr(),
+                )
+            };
+            if rt_code != SolClientReturnCode::Ok as rsolace_sys::solClient_returnCode_t {
+                tracing::warn!(
+                    "unsubscribe temporary topic {} failed",
+                    self.destination.dest
+                );
+            }
+        }
+        tracing::debug!("solace temporary topic {} dropped", self.destination.dest);
+    }
+}
+
+// SAFETY: the session pointer is only used to unsubscribe, which the C API
+// allows from any thread.
+unsafe impl Send for TemporaryTopic {}
+unsafe impl Sync for TemporaryTopic {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solclient::SolClient;
+    use crate::types::SolClientLogLevel;
+
+    #[test]
+    fn test_create_temporary_without_session() {
+        let client = SolClient::new(SolClientLogLevel::Notice).unwrap();
+        assert_eq!(
+            client.create_temporary_queue().err(),
+            Some(TemporaryError::SessionNotConnected)
+        );
+        assert_eq!(
+            client.create_temporary_topic().err(),
+            Some(TemporaryError::SessionNotConnected)
+        );
+    }
+}
@@ -0,0 +1,341 @@
+//! Local transactions.
+//!
+//! A [`TransactedSession`] groups guaranteed publishes and consumes on one
+//! `SolClient` session into a transaction that is committed or rolled back
+//! atomically by the broker.
+
+use super::solflow::{FlowProps, SolFlow, SolFlowError};
+use super::solmsg::SolMsg;
+use super::types::{ErrorInfo, SolClientReturnCode};
+use super::utils::ConvertToCString;
+use enum_primitive::FromPrimitive;
+use snafu::prelude::{ensure, Snafu};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr::{null, null_mut};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Snafu, PartialEq)]
+pub enum TransactedSessionError {
+    #[snafu(display("TransactedSession on a session that is not connected"))]
+    SessionNotConnected,
+    #[snafu(display("TransactedSession create code: {code:?}, Error {error:?}"))]
+    Create {
+        code: SolClientReturnCode,
+        error: ErrorInfo,
+    },
+    #[snafu(display("TransactedSession send msg code: {code:?}, Error {error:?}"))]
+    Send {
+        code: SolClientReturnCode,
+        error: ErrorInfo,
+    },
+    #[snafu(display("TransactedSession commit code: {code:?}, Error {error:?}"))]
+    Commit {
+        code: SolClientReturnCode,
+        error: ErrorInfo,
+    },
+    /// The commit did not go through and the broker rolled the transaction
+    /// back instead. Every message sent and consumed in it will be
+    /// redelivered / must be republished.
+    #[snafu(display("TransactedSession commit rolled back, Error {error:?}"))]
+    RolledBack { error: ErrorInfo },
+    #[snafu(display("TransactedSession rollback code: {code:?}, Error {error:?}"))]
+    Rollback {
+        code: SolClientReturnCode,
+        error: ErrorInfo,
+    },
+}
+
+#[derive(Debug)]
+pub struct TransactedSessionProps {
+    has_publisher: CString,
+    create_message_dispatcher: CString,
+    request_reply_timeout_ms: CString,
+    pub_window_size: CString,
+}
+
+impl TransactedSessionProps {
+    pub fn to_c(&self) -> [*const c_char; 9] {
+        [
+            rsolace_sys::SOLCLIENT_TRANSACTEDSESSION_PROP_HAS_PUBLISHER.as_ptr() as *const c_char,
+            self.has_publisher.as_ptr() as *const c_char,
+            rsolace_sys::SOLCLIENT_TRANSACTEDSESSION_PROP_CREATE_MESSAGE_DISPATCHER.as_ptr()
+                as *const c_char,
+            self.create_message_dispatcher.as_ptr() as *const c_char,
+            rsolace_sys::SOLCLIENT_TRANSACTEDSESSION_PROP_REQUESTREPLY_TIMEOUT_MS.as_ptr()
+                as *const c_char,
+            self.request_reply_timeout_ms.as_ptr() as *const c_char,
+            rsolace_sys::SOLCLIENT_TRANSACTEDSESSION_PROP_PUB_WINDOW_SIZE.as_ptr() as *const c_char,
+            self.pub_window_size.as_ptr() as *const c_char,
+            null(),
+        ]
+    }
+
+    /// Whether messages can be published in the transaction. Disable for
+    /// consume-only transactions to save the publisher flow on the broker.
+    pub fn has_publisher(mut self, has_publisher: bool) -> Self {
+        self.has_publisher = has_publisher.to_cstring();
+        self
+    }
+
+    /// Dispatch the transacted flows' callbacks from a dedicated thread
+    /// instead of the context thread.
+    pub fn create_message_dispatcher(mut self, create: bool) -> Self {
+        self.create_message_dispatcher = create.to_cstring();
+        self
+    }
+
+    /// Timeout for the commit / rollback round trip to the broker.
+    pub fn request_reply_timeout(mut self, request_reply_timeout_ms: u32) -> Self {
+        self.request_reply_timeout_ms = request_reply_timeout_ms.to_cstring();
+        self
+    }
+
+    pub fn pub_window_size(mut self, pub_window_size: u32) -> Self {
+        self.pub_window_size = pub_window_size.to_cstring();
+        self
+    }
+}
+
+impl Default for TransactedSessionProps {
+    fn default() -> Self {
+        Self {
+            has_publisher: true.to_cstring(),
+            create_message_dispatcher: false.to_cstring(),
+            request_reply_timeout_ms: 10000.to_cstring(),
+            pub_window_size: 255.to_cstring(),
+        }
+    }
+}
+
+/// A transacted session on top of a connected `SolClient` session.
+///
+/// Messages sent with [`TransactedSession::send_msg`] and messages received
+/// on flows from [`TransactedSession::create_flow`] are only published /
+/// removed from their queue when [`TransactedSession::commit`] succeeds.
+///
+/// ```ignore
+/// let ts = client.create_transacted_session(TransactedSessionProps::default())?;
+/// let flow = ts.create_flow(FlowProps::default().queue("settlement/in"))?;
+/// let msg = flow.get_msg_receiver().recv()?;
+/// ts.send_msg(&settled(&msg))?;
+/// ts.commit()?;
+/// ```
+///
+/// Commit and rollback block on the broker round trip, so they must not be
+/// called from a message or event callback.
+pub struct TransactedSession {
+    transacted_session_p: rsolace_sys::solClient_opaqueTransactedSession_pt,
+    session_alive: Arc<AtomicBool>,
+    /// Cleared on drop, destroying the transacted session destroys the
+    /// flows created in it.
+    alive: Arc<AtomicBool>,
+}
+
+impl TransactedSession {
+    pub(crate) fn new(
+        session_p: rsolace_sys::solClient_opaqueSession_pt,
+        session_alive: Arc<AtomicBool>,
+        props: &TransactedSessionProps,
+    ) -> Result<TransactedSession, TransactedSessionError> {
+        ensure!(
+            !session_p.is_null() && session_alive.load(Ordering::Acquire),
+            SessionNotConnectedSnafu
+        );
+        let mut props = props.to_c();
+        let mut transacted_session_p: rsolace_sys::solClient_opaqueTransactedSession_pt =
+            null_mut();
+        let rt_code = unsafe {
+            rsolace_sys::solClient_session_createTransactedSession(
+                props.as_mut_ptr(),
+                session_p,
+                &mut transacted_session_p,
+                null_mut(),
+            )
+        };
+        let rt_code = SolClientReturnCode::from_i32(rt_code).unwrap();
+        ensure!(
+            rt_code == SolClientReturnCode::Ok,
+            CreateSnafu {
+                code: rt_code,
+                error: ErrorInfo::take_last().unwrap(),
+            }
+        );
+        Ok(TransactedSession {
+            transacted_session_p,
+            session_alive,
+            alive: Arc::new(AtomicBool::new(true)),
+        })
+    }
+
+    /// The transacted session pointer, unless the parent session was
+    /// destroyed, which destroyed this one too and left it dangling.
+    fn live_ptr(
+        &self,
+    ) -> Result<rsolace_sys::solClient_opaqueTransactedSession_pt, TransactedSessionError> {
+        ensure!(
+            !self.transacted_session_p.is_null() && self.session_alive.load(Ordering::Acquire),
+            SessionNotConnectedSnafu
+        );
+        Ok(self.transacted_session_p)
+    }
+
+    /// Publish `msg` as part of the current transaction.
+    pub fn send_msg(&self, msg: &SolMsg) -> Result<(), TransactedSessionError> {
+        let transacted_session_p = self.live_ptr()?;
+        let rt_code = unsafe {
+            rsolace_sys::solClient_transactedSession_sendMsg(transacted_session_p, msg.get_ptr())
+        };
+        let rt_code = SolClientReturnCode::from_i32(rt_code).unwrap();
+        ensure!(
+            rt_code == SolClientReturnCode::Ok,
+            SendSnafu {
+                code: rt_code,
+                error: ErrorInfo::take_last().unwrap(),
+            }
+        );
+        Ok(())
+    }
+
+    /// Commit the current transaction and start a new one.
+    ///
+    /// Returns [`TransactedSessionError::RolledBack`] when the broker rolled
+    /// the transaction back instead of committing it.
+    pub fn commit(&self) -> Result<(), TransactedSessionError> {
+        let transacted_session_p = self.live_ptr()?;
+        let rt_code =
+            unsafe { rsolace_sys::solClient_transactedSession_commit(transacted_session_p) };
+        match SolClientReturnCode::from_i32(rt_code).unwrap() {
+            SolClientReturnCode::Ok => Ok(()),
+            SolClientReturnCode::Rollback => RolledBackSnafu {
+                error: ErrorInfo::take_last().unwrap(),
+            }
+            .fail(),
+            code => CommitSnafu {
+                code,
+                error: ErrorInfo::take_last().unwrap(),
+            }
+            .fail(),
+        }
+    }
+
+    /// Roll back the current transaction and start a new one.
+    pub fn rollback(&self) -> Result<(), TransactedSessionError> {
+        let transacted_session_p = self.live_ptr()?;
+        let rt_code =
+            unsafe { rsolace_sys::solClient_transactedSession_rollback(transacted_session_p) };
+        let rt_code = SolClientReturnCode::from_i32(rt_code).unwrap();
+        ensure!(
+            rt_code == SolClientReturnCode::Ok,
+            RollbackSnafu {
+                code: rt_code,
+                error: ErrorInfo::take_last().unwrap(),
+            }
+        );
+        Ok(())
+    }
+
+    /// Create a consumer flow whose messages are settled by this
+    /// transaction. Drop the flow before the `TransactedSession`.
+    pub fn create_flow(&self, props: FlowProps) -> Result<SolFlow, SolFlowError> {
+        SolFlow::new_transacted(
+            self.transacted_session_p,
+            vec![self.session_alive.clone(), self.alive.clone()],
+            &props,
+        )
+    }
+
+    pub fn get_session_name(&self) -> Option<String> {
+        let transacted_session_p = self.live_ptr().ok()?;
+        let mut name: [c_char; 64] = [0; 64];
+        let rt_code = unsafe {
+            rsolace_sys::solClient_transactedSession_getSessionName(
+                transacted_session_p,
+                name.as_mut_ptr(),
+                name.len(),
+            )
+        };
+        if rt_code != SolClientReturnCode::Ok as i32 {
+            return None;
+        }
+        Some(
+            unsafe { CStr::from_ptr(name.as_ptr()) }
+                .to_string_lossy()
+                .into_owned(),
+        )
+    }
+
+    /// Null once the parent session was destroyed.
+    pub fn get_ptr(&self) -> rsolace_sys::solClient_opaqueTransactedSession_pt {
+        self.live_ptr().unwrap_or(null_mut())
+    }
+}
+
+impl Drop for TransactedSession {
+    fn drop(&mut self) {
+        self.alive.store(false, Ordering::Release);
+        // Destroying the parent session already destroyed this one.
+        if !self.transacted_session_p.is_null() && self.session_alive.load(Ordering::Acquire) {
+            unsafe {
+                rsolace_sys::solClient_transactedSession_destroy(&mut self.transacted_session_p);
+            }
+        }
+        tracing::debug!("solace transacted session dropped");
+    }
+}
+
+// SAFETY: the C API allows a transacted session to be used from any thread
+// as long as calls are not made concurrently from the context thread.
+unsafe impl Send for TransactedSession {}
+unsafe impl Sync for TransactedSession {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_transacted_session_props() {
+        let c_props = TransactedSessionProps::default()
+            .has_publisher(false)
+            .to_c();
+        let has_publisher = unsafe { CStr::from_ptr(c_props[1]) };
+        assert_eq!(has_publisher.to_str().unwrap(), "0");
+        assert!(c_props.last().unwrap().is_null());
+    }
+
+    #[test]
+    fn test_create_without_session() {
+        let res = TransactedSession::new(
+            null_mut(),
+            Arc::new(AtomicBool::new(false)),
+            &TransactedSessionProps::default(),
+        );
+        assert_eq!(res.err(), Some(TransactedSessionError::SessionNotConnected));
+    }
+
+    #[test]
+    fn test_use_after_session_destroyed() {
+        // The parent session is gone: the pointer must never be used.
+        let ts = TransactedSession {
+            transacted_session_p: std::ptr::NonNull::dangling().as_ptr(),
+            session_alive: Arc::new(AtomicBool::new(false)),
+            alive: Arc::new(AtomicBool::new(true)),
+        };
+        let msg = SolMsg::new().unwrap();
+        assert_eq!(
+            ts.send_msg(&msg),
+            Err(TransactedSessionError::SessionNotConnected)
+        );
+        assert_eq!(
+            ts.commit(),
+            Err(TransactedSessionError::SessionNotConnected)
+        );
+        assert_eq!(
+            ts.rollback(),
+            Err(TransactedSessionError::SessionNotConnected)
+        );
+        assert_eq!(ts.get_session_name(), None);
+        assert!(ts.get_ptr().is_null());
+    }
+}
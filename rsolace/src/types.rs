@@ -142,6 +142,40 @@ impl From<i32> for SolClientSessionEvent {
     }
 }
 
+#[cfg(target_os = "windows")]
+enum_from_primitive! {
+    #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+    #[repr(i32)]
+    pub enum SolClientFlowEvent {
+        UpNotice = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_UP_NOTICE,
+        DownError = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_DOWN_ERROR,
+        BindFailedError = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_BIND_FAILED_ERROR,
+        RejectedMsgError = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_REJECTED_MSG_ERROR,
+        SessionDown = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_SESSION_DOWN,
+        Active = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_ACTIVE,
+        Inactive = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_INACTIVE,
+        Reconnecting = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_RECONNECTING,
+        Reconnected = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_RECONNECTED,
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+enum_from_primitive! {
+    #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+    #[repr(u32)]
+    pub enum SolClientFlowEvent {
+        UpNotice = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_UP_NOTICE,
+        DownError = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_DOWN_ERROR,
+        BindFailedError = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_BIND_FAILED_ERROR,
+        RejectedMsgError = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_REJECTED_MSG_ERROR,
+        SessionDown = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_SESSION_DOWN,
+        Active = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_ACTIVE,
+        Inactive = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_INACTIVE,
+        Reconnecting = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_RECONNECTING,
+        Reconnected = rsolace_sys::solClient_flow_event_SOLCLIENT_FLOW_EVENT_RECONNECTED,
+    }
+}
+
 enum_from_primitive! {
     #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
     #[repr(u32)]
@@ -580,4 +614,13 @@ impl ErrorInfo {
             }
         }
     }
+
+    /// Read the calling thread's last error info from the C API and reset
+    /// it, so a later failure isn't reported with a stale sub code.
+    pub fn take_last() -> Option<Self> {
+        let error_info =
+            Self::from_error_info_ptr(unsafe { rsolace_sys::solClient_getLastErrorInfo() });
+        unsafe { rsolace_sys::solClient_resetLastErrorInfo() };
+        error_info
+    }
 }
@@ -1,4 +1,6 @@
-use std::ffi::CString;
+use super::types::SolClientReturnCode;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
 
 pub trait ConvertToCString {
     fn to_cstring(&self) -> CString;
@@ -32,6 +34,18 @@ pub fn prop2cstr<T: ConvertToCString>(prop: T) -> CString {
     prop.to_cstring()
 }
 
+/// A new UUID string from `solClient_generateUUIDString`, e.g. for
+/// correlation ids. `None` when the API could not generate one.
+pub fn generate_uuid() -> Option<String> {
+    let mut buf = [0 as c_char; rsolace_sys::SOLCLIENT_UUID_STRING_BUFFER_SIZE as usize];
+    let rt_code = unsafe { rsolace_sys::solClient_generateUUIDString(buf.as_mut_ptr(), buf.len()) };
+    if rt_code != SolClientReturnCode::Ok as rsolace_sys::solClient_returnCode_t {
+        return None;
+    }
+    let uuid = unsafe { CStr::from_ptr(buf.as_ptr()) };
+    Some(uuid.to_string_lossy().into_owned())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -53,4 +67,12 @@ mod tests {
         let res = prop2cstr("192.168.0.1");
         assert_eq!(res, CString::new("192.168.0.1").unwrap());
     }
+
+    #[test]
+    fn test_generate_uuid_unique() {
+        let first = generate_uuid().unwrap();
+        let second = generate_uuid().unwrap();
+        assert_eq!(first.len(), 36);
+        assert_ne!(first, second);
+    }
 }
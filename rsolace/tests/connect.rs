@@ -0,0 +1,54 @@
+//! Integration tests for the [`SolClient::connect`] failure path.
+//!
+//! A connect that never reaches a broker must come back as
+//! `SolClientError::Connect` (or `ConnectFailed` on the async path) and
+//! leave the session marked down, so later calls fail fast instead of
+//! waiting on a session that will never come up.
+//!
+//! These tests open a real TCP connection to `127.0.0.1:1`, which is
+//! expected to be refused. That depends on the host network stack, so
+//! they are `#[ignore]` by default. Run them manually with:
+//!
+//! ```bash
+//! cargo test -p rsolace --test connect -- --ignored --nocapture
+//! ```
+
+use rsolace::solclient::{SessionState, SolClient, SolClientError};
+use rsolace::types::SolClientLogLevel;
+use rsolace::SessionProps;
+
+fn unreachable_session_props() -> SessionProps {
+    SessionProps::default()
+        .host("tcp://127.0.0.1:1")
+        .vpn("default")
+        .connect_timeout_ms(100)
+        .connect_retries(0)
+}
+
+#[test]
+#[ignore = "needs a refused TCP connect on 127.0.0.1:1"]
+fn connect_unreachable_returns_error() {
+    let mut client = SolClient::new(SolClientLogLevel::Notice).unwrap();
+    let res = client.connect(unreachable_session_props());
+    assert!(
+        matches!(res, Err(SolClientError::Connect { .. })),
+        "unexpected connect result: {res:?}"
+    );
+    assert_eq!(client.session_state(), SessionState::Down);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[ignore = "needs a refused TCP connect on 127.0.0.1:1"]
+async fn connect_async_unreachable_returns_error() {
+    let mut client = SolClient::new(SolClientLogLevel::Notice).unwrap();
+    let res = client.connect_async(unreachable_session_props()).await;
+    assert!(
+        matches!(
+            res,
+            Err(SolClientError::Connect { .. } | SolClientError::ConnectFailed { .. })
+        ),
+        "unexpected connect result: {res:?}"
+    );
+    assert_eq!(client.session_state(), SessionState::Down);
+}
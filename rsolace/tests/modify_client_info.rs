@@ -88,8 +88,7 @@ fn modify_client_info_then_request_no_race() {
     };
 
     let mut client = SolClient::new(SolClientLogLevel::Notice).expect("SolClient::new");
-    let connected = client.connect(props);
-    assert!(connected, "failed to connect to broker");
+    client.connect(props).expect("failed to connect to broker");
 
     // Give the API a beat to finish session-up bookkeeping.
     std::thread::sleep(Duration::from_millis(200));
@@ -0,0 +1,121 @@
+//! Integration tests for durable topic endpoint flows.
+//!
+//! These tests exercise [`SolClient::rebind_topic_endpoint`]: a flow bound
+//! to a durable topic endpoint is moved to a new topic, after which the
+//! endpoint attracts the new topic only.
+//!
+//! They talk to a real Solace broker, need a client username allowed to
+//! provision endpoints, and are gated on the same env vars used elsewhere
+//! in the project:
+//!
+//! ```text
+//! SOLACE_HOST     e.g. "tcp://localhost:55555"
+//! SOLACE_VPN
+//! SOLACE_USERNAME
+//! SOLACE_PASSWORD
+//! ```
+//!
+//! The tests are `#[ignore]` by default. Run them manually with:
+//!
+//! ```bash
+//! cargo test -p rsolace --test topic_endpoint -- --ignored --nocapture
+//! ```
+
+use std::time::Duration;
+
+use rsolace::solclient::SolClient;
+use rsolace::solendpoint::EndpointProps;
+use rsolace::solflow::FlowProps;
+use rsolace::solmsg::SolMsg;
+use rsolace::types::{SolClientDeliveryMode, SolClientLogLevel};
+use rsolace::SessionProps;
+
+const TOPIC_ENDPOINT: &str = "rsolace-rebind-te";
+const OLD_TOPIC: &str = "rsolace/test/rebind/old";
+const NEW_TOPIC: &str = "rsolace/test/rebind/new";
+
+fn env_or_skip(key: &str) -> Option<String> {
+    match std::env::var(key) {
+        Ok(v) if !v.is_empty() => Some(v),
+        _ => None,
+    }
+}
+
+fn live_session_props() -> Option<SessionProps> {
+    let host = env_or_skip("SOLACE_HOST")?;
+    let vpn = env_or_skip("SOLACE_VPN")?;
+    let username = env_or_skip("SOLACE_USERNAME")?;
+    let password = env_or_skip("SOLACE_PASSWORD")?;
+    Some(
+        SessionProps::default()
+            .host(&host)
+            .vpn(&vpn)
+            .username(&username)
+            .password(&password)
+            .client_name("rsolace-rebind-test")
+            .connect_retries(1)
+            .connect_timeout_ms(5000),
+    )
+}
+
+fn publish_persistent(client: &SolClient, topic: &str) {
+    let mut msg = SolMsg::new().expect("SolMsg::new");
+    msg.set_topic(topic);
+    msg.set_delivery_mode(SolClientDeliveryMode::Persistent);
+    msg.set_binary_attachment(topic.as_bytes());
+    client
+        .send_persistent(&mut msg)
+        .expect("send_persistent")
+        .wait()
+        .expect("broker ack");
+}
+
+#[test]
+#[ignore = "requires live Solace broker; run with --ignored"]
+fn rebind_topic_endpoint_moves_the_subscription() {
+    let _ = dotenvy::dotenv();
+
+    let props = match live_session_props() {
+        Some(p) => p,
+        None => {
+            eprintln!(
+                "skipping: SOLACE_HOST/VPN/USERNAME/PASSWORD not set; run with --ignored after exporting them"
+            );
+            return;
+        }
+    };
+
+    let mut client = SolClient::new(SolClientLogLevel::Notice).expect("SolClient::new");
+    client.connect(props).expect("failed to connect to broker");
+    let endpoint = EndpointProps::topic_endpoint(TOPIC_ENDPOINT);
+    client
+        .provision_endpoint(&endpoint, true)
+        .expect("provision topic endpoint");
+
+    let flow = client
+        .create_flow(FlowProps::default().topic_endpoint(TOPIC_ENDPOINT, OLD_TOPIC))
+        .expect("bind topic endpoint");
+    let flow = client
+        .rebind_topic_endpoint(flow, NEW_TOPIC)
+        .expect("rebind topic endpoint");
+    let receiver = flow.get_msg_receiver();
+
+    publish_persistent(&client, OLD_TOPIC);
+    publish_persistent(&client, NEW_TOPIC);
+
+    let msg = receiver
+        .recv_timeout(Duration::from_secs(5))
+        .expect("message on the new topic");
+    assert_eq!(msg.get_topic().unwrap(), NEW_TOPIC);
+    drop(msg);
+    assert!(
+        receiver.recv_timeout(Duration::from_millis(500)).is_err(),
+        "the endpoint still attracts the old topic"
+    );
+
+    drop(flow);
+    client
+        .deprovision_endpoint(&endpoint)
+        .expect("deprovision topic endpoint");
+    client.disconnect();
+}